@@ -6,5 +6,27 @@ pub const CONFIG_FILENAME: &'static str = "jwordlist.yaml";
 #[derive(Debug, PartialEq, Deserialize)]
 pub struct Config {
     pub jmdict_filename: PathBuf,
+    /// Binary cache of the parsed dictionary, defaults to the dictionary filename with a `.cache` suffix
+    #[serde(default)]
+    pub jmdict_cache_filename: Option<PathBuf>,
+    /// JMnedict file of proper names, searched along with the dictionary if set
+    #[serde(default)]
+    pub jmnedict_filename: Option<PathBuf>,
+    /// KANJIDIC2 file, describing the kanji of the words if set
+    #[serde(default)]
+    pub kanjidic_filename: Option<PathBuf>,
+    /// Tanaka corpus `examples.utf` file, giving example sentences for the words if set
+    #[serde(default)]
+    pub examples_filename: Option<PathBuf>,
     pub listen_bind: String,
 }
+
+impl Config {
+    pub fn jmdict_cache_filename(&self) -> PathBuf {
+        self.jmdict_cache_filename.clone().unwrap_or_else(|| {
+            let mut cache_filename = self.jmdict_filename.clone().into_os_string();
+            cache_filename.push(".cache");
+            cache_filename.into()
+        })
+    }
+}
@@ -0,0 +1,91 @@
+use jmdict::diff::{DictionaryDiff, EntryChange};
+use jmdict::prelude::*;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Parse a dictionary file, plain or compressed.
+fn load_jmdict(filename: &Path) -> Result<JMDict, crate::Error> {
+    let file = File::open(filename)?;
+    eprintln!("Loading {}", filename.display());
+    Ok(JMDict::from_jmdict_any(file)?)
+}
+
+/// Main spelling and reading of an entry, such as `食べる【たべる】`.
+fn entry_label(entry: &JMDictEntry) -> String {
+    let reading = entry
+        .main_reading()
+        .map(jmdict::entry::Reading::string)
+        .unwrap_or("");
+    match entry.main_kanji() {
+        Some(kanji) => format!("{}【{}】", kanji.string(), reading),
+        None => String::from(reading),
+    }
+}
+
+fn change_kind(change: &EntryChange) -> &'static str {
+    match change {
+        EntryChange::Added(_) => "added",
+        EntryChange::Removed(_) => "removed",
+        EntryChange::Modified { .. } => "modified",
+    }
+}
+
+fn change_label(change: &EntryChange) -> String {
+    let entry = change.new_entry().or_else(|| change.old_entry());
+    format!(
+        "{} {}",
+        change.sequence(),
+        entry.map(entry_label).unwrap_or_default()
+    )
+}
+
+fn print_diff(dictionary_diff: &DictionaryDiff) {
+    println!(
+        "{} added, {} removed, {} modified entries",
+        dictionary_diff.added().count(),
+        dictionary_diff.removed().count(),
+        dictionary_diff.modified().count()
+    );
+    for change in dictionary_diff.changes() {
+        let marker = match change {
+            EntryChange::Added(_) => '+',
+            EntryChange::Removed(_) => '-',
+            EntryChange::Modified { .. } => '~',
+        };
+        println!("{} {}", marker, change_label(change));
+        for field_change in change.field_changes() {
+            println!("    {}", field_change);
+        }
+    }
+}
+
+/// Compare two dictionary files and print the changed entries.
+///
+/// When a word list is given, as a JSON array of entry identifiers like the body of
+/// `/api/get_words`, the changes affecting its words are listed too.
+pub fn diff_dict(
+    old_filename: &Path,
+    new_filename: &Path,
+    word_list_filename: Option<&Path>,
+) -> Result<(), crate::Error> {
+    let old_jmdict = load_jmdict(old_filename)?;
+    let new_jmdict = load_jmdict(new_filename)?;
+    let dictionary_diff = jmdict::diff::diff(&old_jmdict, &new_jmdict);
+    print_diff(&dictionary_diff);
+    if let Some(word_list_filename) = word_list_filename {
+        let entry_ids: Vec<JMDictEntryId> =
+            serde_json::from_reader(BufReader::new(File::open(word_list_filename)?))
+                .map_err(crate::Error::from_other)?;
+        let affected_words = dictionary_diff.affected_words(&entry_ids);
+        println!("{} affected words in the word list", affected_words.len());
+        for (entry_id, change) in affected_words {
+            let word = match entry_id {
+                JMDictEntryId::Kanji(word) | JMDictEntryId::Reading(word) => word.to_string(),
+                JMDictEntryId::Sequence(sequence) => sequence.to_string(),
+            };
+            println!("{}: {} {}", word, change_kind(change), change_label(change));
+        }
+    }
+    Ok(())
+}
@@ -3,6 +3,7 @@ pub enum Error {
     IoError(std::io::Error),
     ActixPayloadError(actix_web::error::PayloadError),
     ActixClientSendRequestError(actix_web::client::SendRequestError),
+    JMDictError(jmdict::Error),
     Other(Box<dyn std::error::Error + 'static>),
 }
 
@@ -24,6 +25,12 @@ impl From<actix_web::client::SendRequestError> for Error {
     }
 }
 
+impl From<jmdict::Error> for Error {
+    fn from(error: jmdict::Error) -> Self {
+        Error::JMDictError(error)
+    }
+}
+
 #[allow(dead_code)]
 impl Error {
     pub fn from_other<E: std::error::Error + 'static>(error: E) -> Self {
@@ -34,6 +41,7 @@ impl Error {
             Error::IoError(e) => Some(e),
             Error::ActixPayloadError(_) => None,
             Error::ActixClientSendRequestError(_) => None,
+            Error::JMDictError(e) => Some(e),
             Error::Other(e) => Some(e.as_ref()),
         }
     }
@@ -42,6 +50,7 @@ impl Error {
             Error::IoError(e) => e,
             Error::ActixPayloadError(e) => e,
             Error::ActixClientSendRequestError(e) => e,
+            Error::JMDictError(e) => e,
             Error::Other(ref e) => e,
         }
     }
@@ -1,6 +1,7 @@
 use clap::{App, Arg};
 use std::fs::File;
 mod config;
+mod diff_dict;
 mod error;
 mod server;
 mod update_dict;
@@ -23,6 +24,21 @@ fn main() -> Result<(), Error> {
                 .takes_value(true)
                 .help("Dictionary file update URL"),
         )
+        .arg(
+            Arg::with_name("dict-diff")
+                .long("dict-diff")
+                .value_names(&["OLD", "NEW"])
+                .conflicts_with("dict-update")
+                .help("Compare two dictionary files, print the changed entries and exit"),
+        )
+        .arg(
+            Arg::with_name("word-list")
+                .long("word-list")
+                .value_name("FILE")
+                .requires("dict-diff")
+                .takes_value(true)
+                .help("JSON array of word entry identifiers to check for changes"),
+        )
         .arg(
             Arg::with_name("config")
                 .long("configuration")
@@ -32,6 +48,16 @@ fn main() -> Result<(), Error> {
                 .takes_value(true),
         )
         .get_matches();
+    if let Some(mut diff_filenames) = matches.values_of_os("dict-diff") {
+        let old_filename = std::path::Path::new(diff_filenames.next().unwrap_or_default());
+        let new_filename = std::path::Path::new(diff_filenames.next().unwrap_or_default());
+        let word_list_filename = matches.value_of_os("word-list").map(std::path::Path::new);
+        if let Err(e) = diff_dict::diff_dict(old_filename, new_filename, word_list_filename) {
+            eprintln!("Failed to compare dictionaries: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
     let config_filename: std::path::PathBuf = matches
         .value_of_os("config")
         .map(std::ffi::OsStr::to_os_string)
@@ -58,6 +84,9 @@ fn main() -> Result<(), Error> {
         }
     }
     // run the server
-    server::run_server(config)?;
+    if let Err(e) = server::run_server(config) {
+        eprintln!("Failed to start server: {}", e);
+        std::process::exit(1);
+    }
     Ok(())
 }
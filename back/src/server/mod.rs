@@ -2,68 +2,192 @@ use actix_web::web;
 use futures::future::Future;
 use futures::stream::Stream;
 use jmdict::prelude::*;
+use serde::Deserialize;
 use std::path::Path;
 use std::sync::Arc;
 mod error;
 use self::error::*;
 use super::config::*;
+use super::error::Error;
 
-pub fn run_server(config: Config) -> std::io::Result<()> {
-    let app = Arc::new(JWordListApp::initialize(config));
+pub fn run_server(config: Config) -> Result<(), Error> {
+    let app = Arc::new(JWordListApp::initialize(config)?);
     let app_data = web::Data::new(Arc::clone(&app));
     println!("Listening on http://{}", &app.config.listen_bind);
     actix_web::HttpServer::new(move || {
         actix_web::App::new()
             .register_data(app_data.clone())
             .service(web::resource("/api/get_words").route(web::post().to_async(get_words)))
+            .service(web::resource("/api/entities").route(web::get().to(get_entities)))
+            .service(web::resource("/api/search").route(web::get().to(search)))
+            .service(web::resource("/api/search_words").route(web::get().to(search_words)))
+            .service(web::resource("/api/kanji/{character}").route(web::get().to(get_kanji)))
+            .service(web::resource("/api/get_examples").route(web::post().to(get_examples)))
             .service(actix_files::Files::new("/", "./static").index_file("index.html"))
     })
     .bind(&app.config.listen_bind)?
-    .run()
+    .run()?;
+    Ok(())
 }
 
 struct JWordListApp {
     config: Config,
-    jmdict_memory: Vec<u8>,
+    jmdict: JMDict,
+    jmnedict: JMnedict,
+    kanjidic: Kanjidic,
+    examples: jmdict::examples::ExampleIndex,
 }
 
 impl JWordListApp {
-    fn initialize(config: Config) -> Self {
+    fn initialize(config: Config) -> Result<Self, Error> {
         let jmdict_filename = Path::new(&config.jmdict_filename);
-        let jmdict_memory = Self::load_jmdict_to_memory(jmdict_filename).expect(&format!(
-            "Unable to read JMDict file {}",
-            jmdict_filename.display()
-        ));
-        JWordListApp {
+        let jmdict_cache_filename = config.jmdict_cache_filename();
+        let jmdict = jmdict::cache::load_jmdict_cached(jmdict_filename, &jmdict_cache_filename)
+            .map_err(|e| {
+                eprintln!("Unable to read JMDict file {}", jmdict_filename.display());
+                e
+            })?;
+        println!("Loaded {} dictionary entries", jmdict.len());
+        let jmnedict = match &config.jmnedict_filename {
+            Some(jmnedict_filename) => {
+                let jmnedict = std::fs::File::open(jmnedict_filename)
+                    .map_err(Error::from)
+                    .and_then(|file| JMnedict::from_jmnedict_any(file).map_err(Error::from))
+                    .map_err(|e| {
+                        eprintln!(
+                            "Unable to read JMnedict file {}",
+                            jmnedict_filename.display()
+                        );
+                        e
+                    })?;
+                println!("Loaded {} proper names", jmnedict.len());
+                jmnedict
+            }
+            None => JMnedict::new(),
+        };
+        let kanjidic = match &config.kanjidic_filename {
+            Some(kanjidic_filename) => {
+                let kanjidic = std::fs::File::open(kanjidic_filename)
+                    .map_err(Error::from)
+                    .and_then(|file| Kanjidic::from_kanjidic_any(file).map_err(Error::from))
+                    .map_err(|e| {
+                        eprintln!(
+                            "Unable to read KANJIDIC2 file {}",
+                            kanjidic_filename.display()
+                        );
+                        e
+                    })?;
+                println!("Loaded {} kanji", kanjidic.len());
+                kanjidic
+            }
+            None => Kanjidic::new(),
+        };
+        let examples = match &config.examples_filename {
+            Some(examples_filename) => {
+                let sentences = std::fs::File::open(examples_filename)
+                    .map_err(Error::from)
+                    .and_then(|file| {
+                        jmdict::examples::read_examples(std::io::BufReader::new(file))
+                            .map_err(Error::from)
+                    })
+                    .map_err(|e| {
+                        eprintln!(
+                            "Unable to read examples file {}",
+                            examples_filename.display()
+                        );
+                        e
+                    })?;
+                let examples = jmdict::examples::ExampleIndex::from_sentences(sentences, &jmdict);
+                println!("Loaded {} example sentences", examples.len());
+                examples
+            }
+            None => jmdict::examples::ExampleIndex::new(),
+        };
+        Ok(JWordListApp {
             config,
-            jmdict_memory,
-        }
+            jmdict,
+            jmnedict,
+            kanjidic,
+            examples,
+        })
     }
-    fn load_jmdict_to_memory(jmdict_path: &Path) -> Result<Vec<u8>, std::io::Error> {
-        use std::fs::File;
-        use std::io::Cursor;
-        use std::io::{Seek, SeekFrom};
-        let mut jmdict_file = File::open(jmdict_path)?;
-        let total_size = jmdict_file.seek(SeekFrom::End(0))?;
-        jmdict_file.seek(SeekFrom::Start(0))?;
-        let mut jmdict_memory: Vec<u8> = Vec::with_capacity(total_size as usize);
-        let mut jmdict_memory_cursor = Cursor::new(&mut jmdict_memory);
-        std::io::copy(&mut jmdict_file, &mut jmdict_memory_cursor)?;
-        Ok(jmdict_memory)
+    /// Look up a word and the proper names spelled the same way, with the furigana of each
+    /// candidate aligned to its kanji.
+    fn lookup_word(&self, id: JMDictEntryId, languages: &[String]) -> WordLookup {
+        // sequence numbers of the two dictionaries are unrelated
+        let names: Vec<NameEntry> = match &id {
+            JMDictEntryId::Kanji(_) | JMDictEntryId::Reading(_) => {
+                self.jmnedict.lookup(&id).into_iter().cloned().collect()
+            }
+            JMDictEntryId::Sequence(_) => Vec::new(),
+        };
+        let mut word_lookup = self.lookup_dictionary_word(id, languages);
+        word_lookup.set_names(names);
+        // KANJIDIC2 is only loaded here, so the client cannot align the readings of each kanji
+        word_lookup.align_furigana(&self.kanjidic);
+        word_lookup
     }
-    fn with_entries<'a, I: IntoIterator<Item = JMDictEntryId<'a>>, F: FnMut(JMDictEntry)>(
-        &self,
-        entries_iter: I,
-        f: F,
-    ) -> usize {
-        jmdict::with_jmdict_gz_entries(std::io::Cursor::new(&self.jmdict_memory), entries_iter, f)
+    /// Look up a word, falling back to its dictionary form if it is conjugated.
+    fn lookup_dictionary_word(&self, id: JMDictEntryId, languages: &[String]) -> WordLookup {
+        let candidates: Vec<JMDictEntry> = self
+            .jmdict
+            .lookup(&id)
+            .into_iter()
+            .map(|entry| select_languages(entry, languages))
+            .collect();
+        if !candidates.is_empty() {
+            return WordLookup::new(id.into_owned(), candidates);
+        }
+        let (inflected_candidates, inflections) = match &id {
+            JMDictEntryId::Kanji(word) | JMDictEntryId::Reading(word) => {
+                self.jmdict.lookup_inflected(word)
+            }
+            JMDictEntryId::Sequence(_) => (Vec::new(), Vec::new()),
+        };
+        let inflected_candidates = inflected_candidates
+            .into_iter()
+            .map(|entry| select_languages(entry, languages))
+            .collect();
+        WordLookup::new_inflected(id.into_owned(), inflected_candidates, inflections)
     }
 }
 
+/// Glosses languages asked by the client, as a comma-separated list of ISO 639-2 codes.
+///
+/// Entries without any gloss in these languages are returned with their English glosses.
+#[derive(Debug, Deserialize)]
+struct LanguageParameters {
+    #[serde(default)]
+    lang: Option<String>,
+}
+
+/// Languages of a comma-separated list such as `fre,ger`, all languages if not set.
+fn parse_languages(lang: &Option<String>) -> Vec<String> {
+    lang.iter()
+        .flat_map(|lang| lang.split(','))
+        .map(str::trim)
+        .filter(|language| !language.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Copy of an entry with only the glosses in the given languages, falling back to English for
+/// entries without any.
+///
+/// The dictionary is loaded with the glosses of every language, since each request asks for its
+/// own, so glosses are selected here rather than by the reader.
+fn select_languages(entry: &JMDictEntry, languages: &[String]) -> JMDictEntry {
+    let mut entry = entry.clone();
+    entry.select_languages(languages);
+    entry
+}
+
 fn get_words(
     state: web::Data<Arc<JWordListApp>>,
+    parameters: web::Query<LanguageParameters>,
     payload: web::Payload,
 ) -> impl Future<Item = actix_web::HttpResponse, Error = actix_web::error::Error> {
+    let languages = parse_languages(&parameters.lang);
     payload
         .from_err()
         .fold(web::BytesMut::new(), |mut body, chunk| {
@@ -71,29 +195,152 @@ fn get_words(
             Result::<web::BytesMut, actix_web::error::Error>::Ok(body)
         })
         .and_then(move |body| {
-            use std::collections::HashMap;
             let entries_id_string_total: &str =
                 std::str::from_utf8(&body).map_err(JWordListErrorResponse::from)?;
             let entry_ids: Vec<JMDictEntryId> = serde_json::from_str(&entries_id_string_total)
                 .map_err(JWordListErrorResponse::from)?;
             let jwordlistapp: &JWordListApp = &state;
-            let mut entries_map: HashMap<JMDictEntryId, Vec<JMDictEntry>> = Default::default();
-            jwordlistapp.with_entries(entry_ids.iter().cloned(), |e| {
-                let entry = entries_map
-                    .entry(e.entry_id().clone().into_owned())
-                    .or_insert(Vec::new());
-                entry.push(e);
-            });
-            // reorder according to the original order
-            let entry_list: Vec<&JMDictEntry> = entry_ids
-                .iter()
-                .filter_map(|id| entries_map.get(id))
-                .flatten()
+            let word_lookups: Vec<WordLookup> = entry_ids
+                .into_iter()
+                .map(|id| jwordlistapp.lookup_word(id, &languages))
                 .collect();
             let json_string =
-                serde_json::to_string(&entry_list).map_err(JWordListErrorResponse::from)?;
+                serde_json::to_string(&word_lookups).map_err(JWordListErrorResponse::from)?;
             Ok(actix_web::HttpResponse::Ok()
                 .header(actix_web::http::header::CONTENT_TYPE, "application/json")
                 .body(json_string))
         })
 }
+
+fn get_entities(
+    state: web::Data<Arc<JWordListApp>>,
+) -> Result<actix_web::HttpResponse, actix_web::error::Error> {
+    // name types of proper names are described along with the dictionary tags
+    let mut entities = state.jmdict.entities().clone();
+    for (name, description) in state.jmnedict.entities().iter() {
+        if entities.get(name).is_none() {
+            entities.insert(String::from(name), String::from(description));
+        }
+    }
+    let json_string = serde_json::to_string(&entities).map_err(JWordListErrorResponse::from)?;
+    Ok(actix_web::HttpResponse::Ok()
+        .header(actix_web::http::header::CONTENT_TYPE, "application/json")
+        .body(json_string))
+}
+
+/// Details of a single kanji: readings, meanings, stroke count, grade, JLPT level, frequency and
+/// radical.
+fn get_kanji(
+    state: web::Data<Arc<JWordListApp>>,
+    character: web::Path<String>,
+) -> Result<actix_web::HttpResponse, actix_web::error::Error> {
+    let mut characters = character.chars();
+    let kanji = match (characters.next(), characters.next()) {
+        (Some(literal), None) => state.kanjidic.get(literal),
+        _ => None,
+    };
+    let kanji = match kanji {
+        Some(kanji) => kanji,
+        None => return Ok(actix_web::HttpResponse::NotFound().finish()),
+    };
+    let json_string = serde_json::to_string(kanji).map_err(JWordListErrorResponse::from)?;
+    Ok(actix_web::HttpResponse::Ok()
+        .header(actix_web::http::header::CONTENT_TYPE, "application/json")
+        .body(json_string))
+}
+
+const EXAMPLES_PER_ENTRY: usize = 3;
+
+/// A few example sentences with their translation for each of the entries, by sequence number.
+fn get_examples(
+    state: web::Data<Arc<JWordListApp>>,
+    sequences: web::Json<Vec<u32>>,
+) -> Result<actix_web::HttpResponse, actix_web::error::Error> {
+    let examples: std::collections::BTreeMap<u32, Vec<&jmdict::examples::ExampleSentence>> =
+        sequences
+            .iter()
+            .map(|&sequence| {
+                (
+                    sequence,
+                    state.examples.examples_for(sequence, EXAMPLES_PER_ENTRY),
+                )
+            })
+            .filter(|(_, sentences)| !sentences.is_empty())
+            .collect();
+    let json_string = serde_json::to_string(&examples).map_err(JWordListErrorResponse::from)?;
+    Ok(actix_web::HttpResponse::Ok()
+        .header(actix_web::http::header::CONTENT_TYPE, "application/json")
+        .body(json_string))
+}
+
+const SEARCH_DEFAULT_LIMIT: usize = 50;
+const SEARCH_MAX_LIMIT: usize = 200;
+
+#[derive(Debug, Deserialize)]
+struct SearchParameters {
+    q: String,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    lang: Option<String>,
+}
+
+/// Search entries by English gloss, best matches first.
+fn search(
+    state: web::Data<Arc<JWordListApp>>,
+    parameters: web::Query<SearchParameters>,
+) -> Result<actix_web::HttpResponse, actix_web::error::Error> {
+    let limit = parameters
+        .limit
+        .unwrap_or(SEARCH_DEFAULT_LIMIT)
+        .min(SEARCH_MAX_LIMIT);
+    let languages = parse_languages(&parameters.lang);
+    let entries: Vec<JMDictEntry> = state
+        .jmdict
+        .search_glosses(&parameters.q)
+        .into_iter()
+        .take(limit)
+        .map(|entry| select_languages(entry, &languages))
+        .collect();
+    let json_string = serde_json::to_string(&entries).map_err(JWordListErrorResponse::from)?;
+    Ok(actix_web::HttpResponse::Ok()
+        .header(actix_web::http::header::CONTENT_TYPE, "application/json")
+        .body(json_string))
+}
+
+const SEARCH_WORDS_DEFAULT_PAGE_SIZE: usize = 20;
+const SEARCH_WORDS_MAX_PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct SearchWordsParameters {
+    q: String,
+    #[serde(default)]
+    page: usize,
+    #[serde(default)]
+    page_size: Option<usize>,
+    #[serde(default)]
+    lang: Option<String>,
+}
+
+/// Search entries by kanji or reading pattern, such as `食*`, `*的` or `?べる`, one page at a time.
+fn search_words(
+    state: web::Data<Arc<JWordListApp>>,
+    parameters: web::Query<SearchWordsParameters>,
+) -> Result<actix_web::HttpResponse, actix_web::error::Error> {
+    let page_size = parameters
+        .page_size
+        .unwrap_or(SEARCH_WORDS_DEFAULT_PAGE_SIZE)
+        .min(SEARCH_WORDS_MAX_PAGE_SIZE);
+    let pattern = jmdict::search::WordPattern::parse(&parameters.q);
+    let languages = parse_languages(&parameters.lang);
+    let search_page = jmdict::search::SearchPage::from_results(
+        state.jmdict.search_forms(&pattern),
+        parameters.page,
+        page_size,
+    )
+    .map(|entry| select_languages(entry, &languages));
+    let json_string = serde_json::to_string(&search_page).map_err(JWordListErrorResponse::from)?;
+    Ok(actix_web::HttpResponse::Ok()
+        .header(actix_web::http::header::CONTENT_TYPE, "application/json")
+        .body(json_string))
+}
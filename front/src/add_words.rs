@@ -6,6 +6,7 @@ use wasm_bindgen::JsCast;
 
 thread_local! {
     static SUBMIT_CLOSURE: RefCell<Closure<dyn FnMut() -> bool>> = RefCell::new(Closure::wrap(Box::new(|| action_submit().unwrap()) as Box<dyn FnMut() -> bool>));
+    static CHOOSE_CLOSURE: RefCell<Closure<dyn FnMut(web_sys::Event)>> = RefCell::new(Closure::wrap(Box::new(|event: web_sys::Event| action_choose(event).unwrap()) as Box<dyn FnMut(web_sys::Event)>));
 }
 
 pub fn add_word_form_init() -> Result<(), JsValue> {
@@ -19,6 +20,11 @@ pub fn add_word_form_init() -> Result<(), JsValue> {
         }
         Ok(())
     })?;
+    // set the homograph choice action
+    CHOOSE_CLOSURE.with(|closure| -> Result<(), JsValue> {
+        let add_words_chooser = document.get_element_by_id("add-words-chooser").unwrap();
+        configure_submit(add_words_chooser.into(), &closure.borrow())
+    })?;
     // add the fields to the form
     add_word_fields_reset()?;
     Ok(())
@@ -44,7 +50,7 @@ fn add_new_field(add_words_field: &web_sys::Element) -> Result<web_sys::Element,
     let new_field_name =
         typed_html::types::Id::new(add_field_name(add_words_field.children().length()));
     let my_html: std::boxed::Box<typed_html::elements::p<String>> = html!(
-                <p><input type="text" name=new_field_name style="width: 250px;" placeholder="Type a new word here..."/></p>
+                <p><input type="text" name=new_field_name style="width: 250px;" placeholder="Type a new word here, in kanji, kana or romaji..."/></p>
     );
     let new_element = super::js_util::parse_html_element(&my_html.to_string())?.unwrap();
     add_words_field.append_child(&new_element)?;
@@ -86,17 +92,20 @@ fn configure_submit<T: ?Sized>(node: web_sys::Node, closure: &Closure<T>) -> Res
 
 fn action_submit() -> Result<bool, JsValue> {
     use crate::storage::WordStorage;
+    clear_lookup_notes()?;
     let mut all_dict_entry_ids: Vec<JMDictEntryId<'static>> =
         super::storage::WindowLocalStorage().get_stored_entry_ids()?;
     let fields: Vec<_> = iter_add_word_fields()?.collect();
     all_dict_entry_ids.reserve(fields.len());
-    let field_value_iterator = fields.iter().map(|e| e.value());
+    let field_value_iterator = fields
+        .iter()
+        .map(|e| e.value())
+        .filter(|field_value| !field_value.is_empty());
     for field_value in field_value_iterator {
-        all_dict_entry_ids.push(JMDictEntryId::from_kanji(field_value));
+        all_dict_entry_ids.push(JMDictEntryId::from_word(field_value));
     }
-    super::storage::WindowLocalStorage()
-        .set_stored_entry_ids(all_dict_entry_ids.iter().cloned())?;
     let js_future = super::get_words(all_dict_entry_ids)?
+        .and_then(super::resolve_word_lookups)
         .and_then(super::display_word_list)
         .and_then(|_| {
             add_word_fields_reset()?;
@@ -108,3 +117,229 @@ fn action_submit() -> Result<bool, JsValue> {
     // do not submit
     Ok(false)
 }
+
+/// Short description of an entry for the homograph chooser: its main form and first meaning.
+fn candidate_summary(entry: &JMDictEntry) -> String {
+    let main_reading = entry
+        .main_reading()
+        .map(jmdict::entry::Reading::string)
+        .unwrap_or("");
+    let main_form = match entry.main_kanji() {
+        Some(main_kanji) => format!("{}【{}】", main_kanji.string(), main_reading),
+        None => String::from(main_reading),
+    };
+    let first_meaning: Vec<&str> = entry
+        .senses()
+        .first()
+        .map(|sense| sense.glosses().iter().map(|g| g.text()).collect())
+        .unwrap_or_default();
+    format!("{} {}", main_form, first_meaning.join("; "))
+}
+
+/// Display, for each ambiguous word, the list of homographs the user can choose from.
+///
+/// The choosers previously displayed are replaced, since ambiguous words stay in the word list
+/// until a homograph is chosen. Each chooser keeps the identifier of its word as JSON, to remove
+/// it from the word list once the choice is made.
+pub fn display_word_chooser(word_lookups: &[WordLookup]) -> Result<(), JsValue> {
+    use typed_html::{html, text};
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let add_words_chooser = document.get_element_by_id("add-words-chooser").unwrap();
+    remove_elements_by_class_name(&document, "word-chooser");
+    for word_lookup in word_lookups {
+        let word: String = match word_lookup.entry_id() {
+            JMDictEntryId::Kanji(word) | JMDictEntryId::Reading(word) => word.to_string(),
+            JMDictEntryId::Sequence(sequence) => sequence.to_string(),
+        };
+        let entry_id_json =
+            serde_json::to_string(word_lookup.entry_id()).map_err(|e| e.to_string())?;
+        let chooser_html: std::boxed::Box<typed_html::elements::div<String>> = html!(
+            <div class="word-chooser collection with-header" data-entry-id={ entry_id_json }>
+                <div class="collection-header"><h6>{ text!("Which \"{}\" did you mean?", word) }</h6></div>
+                {
+                    word_lookup.candidates().iter().map(|entry| html!(
+                        <a class="collection-item" style="cursor: pointer;" data-sequence={ entry.sequence().to_string() }>
+                            { text!(candidate_summary(entry)) }
+                        </a>
+                    ))
+                }
+            </div>
+        );
+        if let Some(new_element) = super::js_util::parse_html_element(&chooser_html.to_string())? {
+            add_words_chooser.append_child(&new_element)?;
+        }
+    }
+    Ok(())
+}
+
+/// Display, for each conjugated word, the conjugation recognized and the dictionary form stored.
+pub fn display_inflection_notes(word_lookups: &[WordLookup]) -> Result<(), JsValue> {
+    use typed_html::{html, text};
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let add_words_chooser = document.get_element_by_id("add-words-chooser").unwrap();
+    for word_lookup in word_lookups.iter().filter(|l| l.is_inflected()) {
+        let word: &str = match word_lookup.entry_id() {
+            JMDictEntryId::Kanji(word) | JMDictEntryId::Reading(word) => word,
+            JMDictEntryId::Sequence(_) => continue,
+        };
+        let dictionary_form: &str = match word_lookup.candidates().first() {
+            Some(entry) => entry
+                .main_kanji()
+                .map(jmdict::entry::Kanji::string)
+                .or_else(|| entry.main_reading().map(jmdict::entry::Reading::string))
+                .unwrap_or(""),
+            None => continue,
+        };
+        let inflections = jmdict::deinflect::describe_inflections(word_lookup.inflections());
+        let note_html: std::boxed::Box<typed_html::elements::p<String>> = html!(
+            <p class="inflection-note grey-text">
+                { text!("{}: {} form of {}", word, inflections, dictionary_form) }
+            </p>
+        );
+        if let Some(new_element) = super::js_util::parse_html_element(&note_html.to_string())? {
+            add_words_chooser.append_child(&new_element)?;
+        }
+    }
+    Ok(())
+}
+
+/// Display the words matching no dictionary entry, which are not added to the word list.
+pub fn display_not_found_notes(word_lookups: &[WordLookup]) -> Result<(), JsValue> {
+    use typed_html::{html, text};
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let add_words_chooser = document.get_element_by_id("add-words-chooser").unwrap();
+    for word_lookup in word_lookups.iter().filter(|l| l.candidates().is_empty()) {
+        let word: String = match word_lookup.entry_id() {
+            JMDictEntryId::Kanji(word) | JMDictEntryId::Reading(word) => word.to_string(),
+            JMDictEntryId::Sequence(sequence) => sequence.to_string(),
+        };
+        let note_html: std::boxed::Box<typed_html::elements::p<String>> = html!(
+            <p class="not-found-note red-text">
+                { text!("No dictionary entry found for \"{}\"", word) }
+            </p>
+        );
+        if let Some(new_element) = super::js_util::parse_html_element(&note_html.to_string())? {
+            add_words_chooser.append_child(&new_element)?;
+        }
+    }
+    Ok(())
+}
+
+/// Summary of a proper name, such as `小泉【こいずみ】 Koizumi (family or surname)`.
+fn name_summary(name: &NameEntry) -> String {
+    let main_reading: &str = name
+        .main_reading()
+        .map(jmdict::entry::Reading::string)
+        .unwrap_or("");
+    let heading = match name.main_kanji() {
+        Some(kanji) => format!("{}【{}】", kanji.string(), main_reading),
+        None => String::from(main_reading),
+    };
+    let details: Vec<&str> = name
+        .translations()
+        .iter()
+        .flat_map(|translation| translation.details())
+        .map(jmdict::entry::Gloss::text)
+        .collect();
+    let name_types: Vec<String> = name
+        .name_types()
+        .into_iter()
+        .map(|name_type| super::display_word_list::entity_description(name_type.code()))
+        .collect();
+    format!(
+        "{} {} ({})",
+        heading,
+        details.join("; "),
+        name_types.join(", ")
+    )
+}
+
+/// Display the proper names spelled like each word, from the JMnedict dictionary.
+pub fn display_name_notes(word_lookups: &[WordLookup]) -> Result<(), JsValue> {
+    use typed_html::{html, text};
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let add_words_chooser = document.get_element_by_id("add-words-chooser").unwrap();
+    for word_lookup in word_lookups.iter().filter(|l| !l.names().is_empty()) {
+        let word: &str = match word_lookup.entry_id() {
+            JMDictEntryId::Kanji(word) | JMDictEntryId::Reading(word) => word,
+            JMDictEntryId::Sequence(_) => continue,
+        };
+        let note_html: std::boxed::Box<typed_html::elements::div<String>> = html!(
+            <div class="name-note collection with-header">
+                <div class="collection-header"><h6>{ text!("\"{}\" as a proper name", word) }</h6></div>
+                {
+                    word_lookup.names().iter().map(|name| html!(
+                        <div class="collection-item grey-text">{ text!(name_summary(name)) }</div>
+                    ))
+                }
+            </div>
+        );
+        if let Some(new_element) = super::js_util::parse_html_element(&note_html.to_string())? {
+            add_words_chooser.append_child(&new_element)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove the conjugation, proper name and unknown word notes of previously added words.
+fn clear_lookup_notes() -> Result<(), JsValue> {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    for class_name in &["inflection-note", "name-note", "not-found-note"] {
+        remove_elements_by_class_name(&document, class_name);
+    }
+    Ok(())
+}
+
+fn remove_elements_by_class_name(document: &web_sys::Document, class_name: &str) {
+    let elements = document.get_elements_by_class_name(class_name);
+    for element in super::js_util::html_collection_iter(elements).collect::<Vec<_>>() {
+        element.remove();
+    }
+}
+
+fn action_choose(event: web_sys::Event) -> Result<(), JsValue> {
+    use crate::storage::WordStorage;
+    let candidate_element = match event
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+    {
+        Some(target_element) => target_element.closest("[data-sequence]")?,
+        None => None,
+    };
+    let candidate_element = match candidate_element {
+        Some(candidate_element) => candidate_element,
+        None => return Ok(()),
+    };
+    event.prevent_default();
+    let sequence: u32 = match candidate_element
+        .get_attribute("data-sequence")
+        .and_then(|s| s.parse().ok())
+    {
+        Some(sequence) => sequence,
+        None => return Ok(()),
+    };
+    let mut all_dict_entry_ids: Vec<JMDictEntryId<'static>> =
+        super::storage::WindowLocalStorage().get_stored_entry_ids()?;
+    // the word is no longer ambiguous
+    if let Some(word_chooser) = candidate_element.closest(".word-chooser")? {
+        let chosen_entry_id: Option<JMDictEntryId<'static>> = word_chooser
+            .get_attribute("data-entry-id")
+            .and_then(|json| serde_json::from_str(&json).ok());
+        if let Some(chosen_entry_id) = chosen_entry_id {
+            all_dict_entry_ids.retain(|entry_id| *entry_id != chosen_entry_id);
+        }
+        word_chooser.remove();
+    }
+    all_dict_entry_ids.push(JMDictEntryId::from_sequence(sequence));
+    let js_future = super::get_words(all_dict_entry_ids)?
+        .and_then(super::resolve_word_lookups)
+        .and_then(super::display_word_list)
+        .map_err(super::js_util::map_js_err_to_unit);
+    wasm_bindgen_futures::spawn_local(js_future);
+    Ok(())
+}
@@ -1,16 +1,174 @@
+use jmdict::furigana::FuriganaSegment;
 use jmdict::prelude::*;
-use wasm_bindgen::prelude::*;
 use percent_encoding::{percent_encode, AsciiSet, CONTROLS};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
 
 /// https://url.spec.whatwg.org/#fragment-percent-encode-set
 const ASCII_SET_FRAGMENT: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>').add(b'`');
 
-fn make_word_url(base_url: &str, word: &str) -> String {
-    format!("{}{}", base_url, percent_encode(word.as_bytes(), ASCII_SET_FRAGMENT).to_string())
+thread_local! {
+    static ENTITIES: RefCell<Entities> = RefCell::new(Entities::new());
+    static FURIGANA: RefCell<HashMap<u32, Vec<FuriganaSegment>>> = RefCell::new(HashMap::new());
+}
+
+/// Set the entity definitions used to describe the entry tags.
+pub fn set_entities(entities: Entities) {
+    ENTITIES.with(|my_entities| *my_entities.borrow_mut() = entities);
+}
+
+/// Description of an entity code, or the code itself if it is not defined.
+pub fn entity_description(code: &str) -> String {
+    ENTITIES.with(|entities| String::from(entities.borrow().get(code).unwrap_or(code)))
+}
+
+/// Set the furigana of the main spelling of an entry, aligned to each kanji by the server.
+pub fn set_furigana(sequence: u32, segments: Vec<FuriganaSegment>) {
+    FURIGANA.with(|furigana| furigana.borrow_mut().insert(sequence, segments));
+}
+
+pub fn make_word_url(base_url: &str, word: &str) -> String {
+    format!(
+        "{}{}",
+        base_url,
+        percent_encode(word.as_bytes(), ASCII_SET_FRAGMENT).to_string()
+    )
+}
+
+/// Escape text to be inserted in HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// HTML of the glosses of a sense separated by semicolons, each in a span with a class for its
+/// type such as `gloss-fig`, so figurative and explanatory glosses can be styled differently.
+fn sense_html(sense: &jmdict::entry::Sense) -> String {
+    sense
+        .glosses()
+        .iter()
+        .map(|gloss| {
+            let class = match gloss.gloss_type() {
+                Some(gloss_type) => format!("gloss gloss-{}", gloss_type.code()),
+                None => String::from("gloss"),
+            };
+            format!(
+                "<span class=\"{}\">{}</span>",
+                class,
+                escape_html(gloss.text())
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+/// HTML of a spelling with its reading as `<ruby>` annotations above each run of kanji.
+///
+/// Kana-only words are written as is, and the whole reading is put above the spelling when it
+/// cannot be aligned with the kanji.
+fn ruby_heading(sequence: u32, main_kanji: &str, main_reading: &str) -> String {
+    if main_kanji.is_empty() {
+        return escape_html(main_reading);
+    }
+    // the server aligns a reading to each kanji, the client only to each run of kanji
+    let aligned_segments = FURIGANA
+        .with(|furigana| furigana.borrow().get(&sequence).cloned())
+        .or_else(|| jmdict::furigana::align(main_kanji, main_reading));
+    let segments = match aligned_segments {
+        Some(segments) => segments,
+        None => {
+            return format!(
+                "<ruby>{}<rp>(</rp><rt>{}</rt><rp>)</rp></ruby>",
+                escape_html(main_kanji),
+                escape_html(main_reading)
+            )
+        }
+    };
+    segments
+        .iter()
+        .map(|segment| match segment.reading() {
+            Some(reading) => format!(
+                "<ruby>{}<rp>(</rp><rt>{}</rt><rp>)</rp></ruby>",
+                escape_html(segment.text()),
+                escape_html(reading)
+            ),
+            None => escape_html(segment.text()),
+        })
+        .collect()
+}
+
+/// Other common spellings of an entry, each followed by the readings that apply to it.
+fn other_forms_string(entry: &JMDictEntry, main_kanji: &str) -> String {
+    entry
+        .kanji()
+        .iter()
+        .filter(|k| !k.is_rare() && k.string() != main_kanji)
+        .map(|k| {
+            let readings: Vec<&str> = entry
+                .readings_for_kanji(k)
+                .filter(|r| !r.is_rare())
+                .map(jmdict::entry::Reading::string)
+                .collect();
+            format!("{}【{}】", k.string(), readings.join("、"))
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Part of speech and miscellaneous tags of all the senses of an entry, without duplicates.
+///
+/// Each tag is returned as its code and its description.
+fn entry_tags(entry: &JMDictEntry) -> Vec<(String, String)> {
+    ENTITIES.with(|entities| {
+        let entities = entities.borrow();
+        let mut tags: Vec<(String, String)> = Vec::new();
+        for sense in entry.senses() {
+            let pos_tags = sense
+                .parts_of_speech()
+                .iter()
+                .map(|pos| (pos.code(), pos.description(&entities)));
+            let misc_tags = sense
+                .misc()
+                .iter()
+                .map(|misc| (misc.code(), misc.description(&entities)));
+            for (code, description) in pos_tags.chain(misc_tags) {
+                if !tags.iter().any(|(tag_code, _)| tag_code == code) {
+                    tags.push((
+                        String::from(code),
+                        String::from(description.unwrap_or(code)),
+                    ));
+                }
+            }
+        }
+        tags
+    })
+}
+
+/// Conjugation table of a verb or adjective entry, as form descriptions with their affirmative and negative conjugations.
+fn conjugation_rows(entry: &JMDictEntry, word: &str) -> Vec<(&'static str, String, String)> {
+    jmdict::conjugate::conjugating_part_of_speech(entry)
+        .and_then(|part_of_speech| jmdict::conjugate::conjugate(word, part_of_speech))
+        .map(|table| {
+            table
+                .rows()
+                .iter()
+                .map(|row| {
+                    (
+                        row.form().description(),
+                        String::from(row.affirmative()),
+                        String::from(row.negative().unwrap_or("")),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 pub fn display_word_list(entry_list: &[JMDictEntry]) -> Result<(), JsValue> {
-    use typed_html::{html, text};
+    use typed_html::{html, text, unsafe_text};
     let window = web_sys::window().unwrap();
     let document = window.document().unwrap();
     let word_list_container = document.get_element_by_id("word-list-container").unwrap();
@@ -20,42 +178,64 @@ pub fn display_word_list(entry_list: &[JMDictEntry]) -> Result<(), JsValue> {
 
     let entries_html: Vec<std::boxed::Box<typed_html::elements::li<String>>> =
         entry_list.iter().map(|entry| {
-            let main_kanji: &str = entry.kanji().first().map(jmdict::entry::Kanji::string).unwrap_or("");
-            let main_reading: &str = entry.readings().first().map(jmdict::entry::Reading::string).unwrap_or("");
-            let jisho_url = make_word_url("https://jisho.org/word/", main_kanji);
-            let tangorin_url = make_word_url("https://tangorin.com/words/", main_kanji);
+            let main_kanji: &str = entry.main_kanji().map(jmdict::entry::Kanji::string).unwrap_or("");
+            let main_reading: &str = entry.main_reading().map(jmdict::entry::Reading::string).unwrap_or("");
+            let other_forms = other_forms_string(entry, main_kanji);
+            // kana-only words are headed by their reading
+            let main_heading = if main_kanji.is_empty() { main_reading } else { main_kanji };
+            let main_heading_html = ruby_heading(entry.sequence(), main_kanji, main_reading);
+            let jisho_url = make_word_url("https://jisho.org/word/", main_heading);
+            let tangorin_url = make_word_url("https://tangorin.com/words/", main_heading);
+            let kanji_characters: Vec<String> = main_kanji
+                .chars()
+                .filter(|&c| jmdict::kanji::is_kanji(c))
+                .map(|c| c.to_string())
+                .collect();
+            let examples_id = format!("examples-{}", entry.sequence());
+            let conjugations = conjugation_rows(entry, main_heading);
+            let has_conjugations = !conjugations.is_empty();
             html! {
                 <li class="collection-item">
                     <div class="row">
-                        <div class="col s4 m2"><h5>{ text!( main_kanji ) }</h5><h6 class="grey-text">{ text!( main_reading ) }</h6></div>
+                        <div class="col s4 m2"><h5>{ unsafe_text!( main_heading_html ) }</h5><p class="grey-text">{ text!( other_forms ) }</p>
+                            <p class="kanji-links">
+                                { kanji_characters.iter().map(|kanji| html!(
+                                    <a class="chip kanji-link" title="Kanji details" href={ make_word_url("api/kanji/", kanji) } data-kanji={ kanji.to_string() }>{ text!(kanji.as_str()) }</a>
+                                )) }
+                            </p>
+                        </div>
                         <div class="col s12 m8"><ol>
                             {
-                                entry.senses().iter().filter_map(|sense| {
-                                    let mut sense_string = String::new();
-                                    let mut separator = "";
-                                    let mut has_english = false;
-                                    for gloss in sense.glosses() {
-                                        sense_string.push_str(separator);
-                                        sense_string.push_str(gloss.text());
-                                        separator = "; ";
-                                        has_english = has_english || gloss.lang().is_none();
-                                    }
-                                    if has_english {
-                                        Some(sense_string)
-                                    }
-                                    else {
-                                        None
-                                    }
-                                })
-                                .map(|sense_string| html!(
-                                    <li class="flow-text"> { text!(sense_string) } </li>
+                                // the server only sends the glosses in the preferred languages
+                                entry.senses().iter().filter(|sense| !sense.glosses().is_empty()).map(sense_html)
+                                .map(|sense_html| html!(
+                                    <li class="flow-text"> { unsafe_text!(sense_html) } </li>
                                 ))
                             }
                         </ol></div>
                         <div class="col s12 m2">
-                            <div class="col s12 m2"><span class="badge">"tag1"</span><span class="badge">"tag2"</span></div>
+                            <div class="col s12 m2">
+                                { if entry.is_common() { Some(html!(<span class="new badge teal" data-badge-caption="" title="Common word">"common word"</span>)) } else { None } }
+                                { entry_tags(entry).into_iter().map(|(code, description)| html!(<span class="badge" title=description>{ text!(code) }</span>)) }
+                            </div>
                         </div>
                     </div>
+                    <div class="row examples" id={ examples_id }></div>
+                    { if has_conjugations { Some(html!(
+                        <div class="row">
+                            <details class="col s12 conjugations">
+                                <summary class="grey-text">"Conjugations"</summary>
+                                <table class="striped">
+                                    <thead><tr><th>"Form"</th><th>"Affirmative"</th><th>"Negative"</th></tr></thead>
+                                    <tbody>
+                                        { conjugations.into_iter().map(|(form, affirmative, negative)| html!(
+                                            <tr><td>{ text!(form) }</td><td>{ text!(affirmative) }</td><td>{ text!(negative) }</td></tr>
+                                        )) }
+                                    </tbody>
+                                </table>
+                            </details>
+                        </div>
+                    )) } else { None } }
                     <div class="row">
                         <div class="col s4"><a class="waves-effect waves-light btn-small teal" target="_blank" href={ &jisho_url }>"Jisho"</a></div>
                         <div class="col s4"><a class="waves-effect waves-light btn-small teal" target="_blank" href={ &tangorin_url }>"Tangorin"</a></div>
@@ -85,3 +265,27 @@ pub fn display_word_list(entry_list: &[JMDictEntry]) -> Result<(), JsValue> {
 
     Ok(())
 }
+
+/// Display the example sentences of the entries of the word list, below their definition.
+pub fn display_examples(examples: &super::EntryExamples) -> Result<(), JsValue> {
+    use typed_html::{html, text};
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    for (sequence, sentences) in examples {
+        let examples_element = match document.get_element_by_id(&format!("examples-{}", sequence)) {
+            Some(examples_element) => examples_element,
+            None => continue,
+        };
+        let examples_html: std::boxed::Box<typed_html::elements::ul<String>> = html!(
+            <ul class="col s12">
+                {
+                    sentences.iter().map(|sentence| html!(
+                        <li><span>{ text!(sentence.japanese()) }</span><br/><span class="grey-text">{ text!(sentence.english()) }</span></li>
+                    ))
+                }
+            </ul>
+        );
+        examples_element.set_inner_html(&examples_html.to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use futures::future::Future;
+use jmdict::prelude::*;
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+thread_local! {
+    static KANJI_CLOSURE: RefCell<Closure<dyn FnMut(web_sys::Event)>> = RefCell::new(Closure::wrap(Box::new(|event: web_sys::Event| action_show_kanji(event).unwrap()) as Box<dyn FnMut(web_sys::Event)>));
+}
+
+/// Show the details of a kanji when one of the kanji links of the word list is clicked.
+pub fn kanji_details_init() -> Result<(), JsValue> {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    KANJI_CLOSURE.with(|closure| {
+        let word_list = document.get_element_by_id("word-list").unwrap();
+        if let Some(html_element) = word_list.dyn_ref::<web_sys::HtmlElement>() {
+            html_element.set_onclick(Some(closure.borrow().as_ref().unchecked_ref()));
+        }
+    });
+    Ok(())
+}
+
+fn action_show_kanji(event: web_sys::Event) -> Result<(), JsValue> {
+    let kanji_element = match event
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+    {
+        Some(target_element) => target_element.closest("[data-kanji]")?,
+        None => None,
+    };
+    let literal: char = match kanji_element
+        .and_then(|kanji_element| kanji_element.get_attribute("data-kanji"))
+        .and_then(|kanji| kanji.chars().next())
+    {
+        Some(literal) => literal,
+        None => return Ok(()),
+    };
+    event.prevent_default();
+    let js_future = super::get_kanji(literal)?
+        .and_then(|kanji| display_kanji_details(&kanji))
+        .map_err(super::js_util::map_js_err_to_unit);
+    wasm_bindgen_futures::spawn_local(js_future);
+    Ok(())
+}
+
+fn join_or_dash(strings: &[String]) -> String {
+    if strings.is_empty() {
+        String::from("-")
+    } else {
+        strings.join("、")
+    }
+}
+
+fn number_or_dash<N: ToString>(number: Option<N>) -> String {
+    number.map_or_else(|| String::from("-"), |n| n.to_string())
+}
+
+/// Display the readings, meanings and classification of a kanji above the word list.
+pub fn display_kanji_details(kanji: &KanjiCharacter) -> Result<(), JsValue> {
+    use typed_html::{html, text};
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let kanji_details = document.get_element_by_id("kanji-details").unwrap();
+    let meanings: Vec<&str> = kanji
+        .meanings()
+        .iter()
+        .filter(|meaning| meaning.lang().is_none())
+        .map(jmdict::entry::Gloss::text)
+        .collect();
+    let details_html: std::boxed::Box<typed_html::elements::div<String>> = html!(
+        <div class="card">
+            <div class="card-content row">
+                <div class="col s4 m2"><h2>{ text!(kanji.literal().to_string()) }</h2></div>
+                <div class="col s8 m10">
+                    <p class="flow-text">{ text!(meanings.join(", ")) }</p>
+                    <p>{ text!("On: {}", join_or_dash(kanji.on_readings())) }</p>
+                    <p>{ text!("Kun: {}", join_or_dash(kanji.kun_readings())) }</p>
+                    <p class="grey-text">
+                        { text!("{} strokes, grade {}, old JLPT level {}, frequency {}, radical {}",
+                            number_or_dash(kanji.stroke_count()),
+                            number_or_dash(kanji.grade()),
+                            number_or_dash(kanji.jlpt()),
+                            number_or_dash(kanji.frequency()),
+                            number_or_dash(kanji.radical())) }
+                    </p>
+                </div>
+            </div>
+        </div>
+    );
+    kanji_details.set_inner_html("");
+    if let Some(new_element) = super::js_util::parse_html_element(&details_html.to_string())? {
+        kanji_details.append_child(&new_element)?;
+    }
+    Ok(())
+}
@@ -11,8 +11,12 @@ use self::loading::JWordListLoading;
 mod add_words;
 mod display_word_list;
 mod js_util;
+mod kanji_details;
 mod storage;
 
+/// Example sentences of entries, by sequence number.
+pub type EntryExamples = std::collections::BTreeMap<u32, Vec<jmdict::examples::ExampleSentence>>;
+
 // Called when the wasm module is instantiated
 #[wasm_bindgen(start)]
 pub fn main() -> Result<(), JsValue> {
@@ -27,14 +31,20 @@ pub fn initialize_app() -> Result<(), JsValue> {
     add_words::setup_add_words()?;
     let stored_words = storage::WindowLocalStorage().get_stored_entry_ids()?;
     add_words::add_word_form_init()?;
+    kanji_details::kanji_details_init()?;
+    language_select_init()?;
     let _ = JWordListLoading::lock();
+    let entities_future = get_entities()?.map(display_word_list::set_entities);
     if stored_words.is_empty() {
         // ask for new words
         display_word_list::display_word_list(&[])?;
         add_words::focus_next_add_word_field()?;
+        wasm_bindgen_futures::spawn_local(entities_future.map_err(js_util::map_js_err_to_unit));
     } else {
         // display initial words
-        let js_future = get_words(stored_words)?
+        let js_future = entities_future
+            .join(get_words(stored_words)?)
+            .and_then(|(_, word_lookups)| resolve_word_lookups(word_lookups))
             .and_then(display_word_list)
             .map_err(js_util::map_js_err_to_unit);
         wasm_bindgen_futures::spawn_local(js_future);
@@ -42,10 +52,43 @@ pub fn initialize_app() -> Result<(), JsValue> {
     Ok(())
 }
 
+thread_local! {
+    static LANGUAGE_CLOSURE: std::cell::RefCell<Closure<dyn FnMut()>> = std::cell::RefCell::new(Closure::wrap(Box::new(|| action_select_language().unwrap()) as Box<dyn FnMut()>));
+}
+
+/// Show the gloss language preference in its selector, and store it when it changes.
+fn language_select_init() -> Result<(), JsValue> {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let languages = storage::WindowLocalStorage().get_gloss_languages()?;
+    let language_select = match document.get_element_by_id("gloss-language") {
+        Some(element) => element.dyn_into::<web_sys::HtmlSelectElement>()?,
+        None => return Ok(()),
+    };
+    if let Some(language) = languages.first() {
+        language_select.set_value(language);
+    }
+    LANGUAGE_CLOSURE.with(|closure| {
+        language_select.set_onchange(Some(closure.borrow().as_ref().unchecked_ref()));
+    });
+    Ok(())
+}
+
+/// Store the selected gloss language and display the word list again with it.
+fn action_select_language() -> Result<(), JsValue> {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let language_select = document
+        .get_element_by_id("gloss-language")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlSelectElement>()?;
+    storage::WindowLocalStorage().set_gloss_languages(&[language_select.value()])?;
+    window.location().reload()
+}
+
 pub fn get_words<'a, I: IntoIterator<Item = JMDictEntryId<'a>>>(
     words_iterator: I,
-) -> Result<impl Future<Item = Vec<JMDictEntry>, Error = JsValue>, JsValue> {
-    let mut _loading = JWordListLoading::lock();
+) -> Result<impl Future<Item = Vec<WordLookup>, Error = JsValue>, JsValue> {
     let mut opts = RequestInit::new();
     opts.method("POST");
     opts.mode(RequestMode::SameOrigin);
@@ -55,12 +98,79 @@ pub fn get_words<'a, I: IntoIterator<Item = JMDictEntryId<'a>>>(
         opts.body(Some(&words_json.into()));
     }
 
-    let request = Request::new_with_str_and_init("api/get_words", &opts)?;
+    let languages = storage::WindowLocalStorage().get_gloss_languages()?;
+    let url = format!("api/get_words?lang={}", languages.join(","));
+    let request = Request::new_with_str_and_init(&url, &opts)?;
+
+    request.headers().set("Accept", "application/json")?;
+
+    let words_future = fetch_json(request).map(|json| -> Vec<WordLookup> {
+        // Use serde to parse the JSON into a struct.
+        json.into_serde().unwrap()
+    });
+    Ok(words_future)
+}
+
+/// Fetch the entity definitions describing the dictionary tags.
+pub fn get_entities() -> Result<impl Future<Item = Entities, Error = JsValue>, JsValue> {
+    let mut opts = RequestInit::new();
+    opts.method("GET");
+    opts.mode(RequestMode::SameOrigin);
+
+    let request = Request::new_with_str_and_init("api/entities", &opts)?;
+
+    request.headers().set("Accept", "application/json")?;
+
+    let entities_future =
+        fetch_json(request).map(|json| -> Entities { json.into_serde().unwrap() });
+    Ok(entities_future)
+}
+
+/// Fetch a few example sentences for each entry, by sequence number.
+pub fn get_examples(
+    sequences: Vec<u32>,
+) -> Result<impl Future<Item = EntryExamples, Error = JsValue>, JsValue> {
+    let mut opts = RequestInit::new();
+    opts.method("POST");
+    opts.mode(RequestMode::SameOrigin);
+    {
+        let sequences_json: String =
+            serde_json::to_string(&sequences).map_err(|e| e.to_string())?;
+        opts.body(Some(&sequences_json.into()));
+    }
+
+    let request = Request::new_with_str_and_init("api/get_examples", &opts)?;
 
     request.headers().set("Accept", "application/json")?;
+    request.headers().set("Content-Type", "application/json")?;
+
+    let examples_future =
+        fetch_json(request).map(|json| -> EntryExamples { json.into_serde().unwrap() });
+    Ok(examples_future)
+}
+
+/// Fetch the details of a kanji.
+pub fn get_kanji(
+    literal: char,
+) -> Result<impl Future<Item = KanjiCharacter, Error = JsValue>, JsValue> {
+    let mut opts = RequestInit::new();
+    opts.method("GET");
+    opts.mode(RequestMode::SameOrigin);
+
+    let kanji_url = display_word_list::make_word_url("api/kanji/", &literal.to_string());
+    let request = Request::new_with_str_and_init(&kanji_url, &opts)?;
+
+    request.headers().set("Accept", "application/json")?;
+
+    let kanji_future =
+        fetch_json(request).map(|json| -> KanjiCharacter { json.into_serde().unwrap() });
+    Ok(kanji_future)
+}
 
+fn fetch_json(request: Request) -> impl Future<Item = JsValue, Error = JsValue> {
+    let mut _loading = JWordListLoading::lock();
     let window = web_sys::window().expect("no global `window` exists");
-    let words_future = JsFuture::from(window.fetch_with_request(&request))
+    JsFuture::from(window.fetch_with_request(&request))
         .and_then(move |resp_value| {
             // `resp_value` is a `Response` object.
             assert!(resp_value.is_instance_of::<Response>());
@@ -72,17 +182,76 @@ pub fn get_words<'a, I: IntoIterator<Item = JMDictEntryId<'a>>>(
             // Convert this other `Promise` into a rust `Future`.
             JsFuture::from(json_value)
         })
-        .map(|json| -> Vec<JMDictEntry> {
-            // Use serde to parse the JSON into a struct.
-            json.into_serde().unwrap()
-        });
-    Ok(words_future)
 }
 
+/// Separate the words matching a single entry from the ambiguous ones and the unknown ones.
+///
+/// A chooser is displayed for each ambiguous word, and the sequence identifiers of the other
+/// entries are stored, so that the stored word list points at exact dictionary entries. Ambiguous
+/// words are stored as typed until the user chooses one of their homographs. Conjugated words are
+/// stored as their dictionary form, with a note of the conjugation recognized. Words matching no
+/// entry are noted and dropped.
+pub fn resolve_word_lookups(
+    word_lookups: Vec<WordLookup>,
+) -> impl Future<Item = Vec<JMDictEntry>, Error = JsValue> {
+    use storage::WordStorage;
+    if let Err(e) = add_words::display_inflection_notes(&word_lookups)
+        .and_then(|()| add_words::display_name_notes(&word_lookups))
+        .and_then(|()| add_words::display_not_found_notes(&word_lookups))
+    {
+        return futures::future::err(e);
+    }
+    let (ambiguous_lookups, resolved_lookups): (Vec<WordLookup>, Vec<WordLookup>) =
+        word_lookups.into_iter().partition(WordLookup::is_ambiguous);
+    let ambiguous_entry_ids: Vec<JMDictEntryId<'static>> = ambiguous_lookups
+        .iter()
+        .map(|word_lookup| word_lookup.entry_id().clone())
+        .collect();
+    let mut entry_list: Vec<JMDictEntry> = Vec::with_capacity(resolved_lookups.len());
+    for word_lookup in &resolved_lookups {
+        for (index, entry) in word_lookup.candidates().iter().enumerate() {
+            if let Some(segments) = word_lookup.furigana(index) {
+                display_word_list::set_furigana(entry.sequence(), segments.to_vec());
+            }
+        }
+    }
+    for entry in resolved_lookups
+        .into_iter()
+        .flat_map(WordLookup::into_candidates)
+    {
+        if !entry_list.iter().any(|e| e.sequence() == entry.sequence()) {
+            entry_list.push(entry);
+        }
+    }
+    let result = add_words::display_word_chooser(&ambiguous_lookups).and_then(|()| {
+        let entry_ids = entry_list
+            .iter()
+            .map(JMDictEntry::entry_id)
+            .chain(ambiguous_entry_ids);
+        storage::WindowLocalStorage().set_stored_entry_ids(entry_ids)
+    });
+    match result {
+        Ok(()) => futures::future::ok(entry_list),
+        Err(e) => futures::future::err(e),
+    }
+}
+
+/// Display the word list, then fetch and display the example sentences of its entries.
 pub fn display_word_list<S: AsRef<[JMDictEntry]>>(
     entry_list: S,
 ) -> impl Future<Item = (), Error = JsValue> {
-    match display_word_list::display_word_list(entry_list.as_ref()) {
+    let entry_list = entry_list.as_ref();
+    let result = display_word_list::display_word_list(entry_list).and_then(|()| {
+        if !entry_list.is_empty() {
+            let sequences = entry_list.iter().map(JMDictEntry::sequence).collect();
+            let examples_future = get_examples(sequences)?
+                .and_then(|examples| display_word_list::display_examples(&examples))
+                .map_err(js_util::map_js_err_to_unit);
+            wasm_bindgen_futures::spawn_local(examples_future);
+        }
+        Ok(())
+    });
+    match result {
         Ok(()) => futures::future::ok(()),
         Err(e) => futures::future::err(e),
     }
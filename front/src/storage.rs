@@ -57,3 +57,44 @@ impl WordStorage for WindowLocalStorage {
         Ok(())
     }
 }
+
+const WINDOW_LOCAL_STORAGE_LANGUAGES_KEY: &'static str = "GLOSS_LANGUAGES";
+
+/// ISO 639-1 codes of the browser languages with JMdict glosses, and their ISO 639-2 code.
+const BROWSER_LANGUAGES: &'static [(&'static str, &'static str)] = &[
+    ("de", "ger"),
+    ("en", "eng"),
+    ("es", "spa"),
+    ("fr", "fre"),
+    ("hu", "hun"),
+    ("nl", "dut"),
+    ("ru", "rus"),
+    ("sl", "slv"),
+    ("sv", "swe"),
+];
+
+impl WindowLocalStorage {
+    /// ISO 639-2 languages of the glosses to display, from the browser language by default.
+    pub fn get_gloss_languages(&self) -> Result<Vec<String>, JsValue> {
+        let window = web_sys::window().unwrap();
+        if let Some(local_storage) = window.local_storage()? {
+            if let Some(languages) = local_storage.get_item(WINDOW_LOCAL_STORAGE_LANGUAGES_KEY)? {
+                return Ok(languages.split(',').map(String::from).collect());
+            }
+        }
+        let browser_language = window.navigator().language().unwrap_or_default();
+        let language = BROWSER_LANGUAGES
+            .iter()
+            .find(|(code, _)| browser_language.starts_with(code))
+            .map_or(jmdict::entry::ENGLISH, |&(_, language)| language);
+        Ok(vec![String::from(language)])
+    }
+    pub fn set_gloss_languages(&self, languages: &[String]) -> Result<(), JsValue> {
+        let window = web_sys::window().unwrap();
+        let local_storage: web_sys::Storage = window
+            .local_storage()?
+            .expect("No window local storage available");
+        local_storage.set_item(WINDOW_LOCAL_STORAGE_LANGUAGES_KEY, &languages.join(","))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use super::dictionary::JMDict;
+use super::entry::JMDictEntry;
+use super::tags::Entities;
+use super::Error;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Cursor, Read, Write};
+use std::path::Path;
+
+const CACHE_MAGIC: &[u8; 8] = b"JMDCACHE";
+const CACHE_VERSION: u32 = 6;
+
+/// Identifies the contents of a dictionary source file.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct SourceFingerprint {
+    size: u64,
+    modified: Option<(u64, u32)>,
+    hash: u64,
+}
+
+impl SourceFingerprint {
+    pub fn new(source_data: &[u8], metadata: &std::fs::Metadata) -> Self {
+        use std::time::UNIX_EPOCH;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| (duration.as_secs(), duration.subsec_nanos()));
+        SourceFingerprint {
+            size: metadata.len(),
+            modified,
+            hash: fnv1a_hash(source_data),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CacheHeader {
+    version: u32,
+    source: SourceFingerprint,
+}
+
+/// 64-bit FNV-1a hash: stable across platforms and compiler versions, unlike `DefaultHasher`.
+fn fnv1a_hash(data: &[u8]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+    data.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+fn bincode_to_io_error(error: bincode::Error) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+}
+
+/// Load a JMdict file, plain or gzip, xz or zstd compressed, through its binary cache.
+///
+/// The cache is rebuilt whenever the source file size, modification time or contents change.
+/// Failing to write the cache is not an error.
+pub fn load_jmdict_cached<P: AsRef<Path>, Q: AsRef<Path>>(
+    source_path: P,
+    cache_path: Q,
+) -> Result<JMDict, Error> {
+    let source_path = source_path.as_ref();
+    let cache_path = cache_path.as_ref();
+    let metadata = std::fs::metadata(source_path)?;
+    let mut source_data = Vec::with_capacity(metadata.len() as usize);
+    File::open(source_path)?.read_to_end(&mut source_data)?;
+    let fingerprint = SourceFingerprint::new(&source_data, &metadata);
+    if let Ok(Some(jmdict)) = read_cache(cache_path, &fingerprint) {
+        return Ok(jmdict);
+    }
+    let jmdict = JMDict::from_jmdict_any(Cursor::new(&source_data))?;
+    // the dictionary is usable without its cache, such as in a read-only directory
+    let _ = write_cache(cache_path, &fingerprint, &jmdict);
+    Ok(jmdict)
+}
+
+/// Read a dictionary cache file, returning `None` if it was built from another source.
+pub fn read_cache<P: AsRef<Path>>(
+    cache_path: P,
+    fingerprint: &SourceFingerprint,
+) -> std::io::Result<Option<JMDict>> {
+    let mut cache_reader = BufReader::new(File::open(cache_path)?);
+    let mut magic = [0u8; 8];
+    cache_reader.read_exact(&mut magic)?;
+    if &magic != CACHE_MAGIC {
+        return Ok(None);
+    }
+    let header: CacheHeader =
+        bincode::deserialize_from(&mut cache_reader).map_err(bincode_to_io_error)?;
+    if header.version != CACHE_VERSION || &header.source != fingerprint {
+        return Ok(None);
+    }
+    let entities: Entities =
+        bincode::deserialize_from(&mut cache_reader).map_err(bincode_to_io_error)?;
+    let entries: Vec<JMDictEntry> =
+        bincode::deserialize_from(&mut cache_reader).map_err(bincode_to_io_error)?;
+    let mut jmdict = JMDict::from_entries(entries);
+    jmdict.set_entities(entities);
+    Ok(Some(jmdict))
+}
+
+/// Write a dictionary cache file, replacing any previous cache atomically.
+///
+/// The temporary file is removed if the cache cannot be written.
+pub fn write_cache<P: AsRef<Path>>(
+    cache_path: P,
+    fingerprint: &SourceFingerprint,
+    jmdict: &JMDict,
+) -> std::io::Result<()> {
+    let cache_path = cache_path.as_ref();
+    let mut temporary_path = cache_path.as_os_str().to_os_string();
+    temporary_path.push(".tmp");
+    let result = write_cache_file(Path::new(&temporary_path), fingerprint, jmdict)
+        .and_then(|()| std::fs::rename(&temporary_path, cache_path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temporary_path);
+    }
+    result
+}
+
+fn write_cache_file(
+    path: &Path,
+    fingerprint: &SourceFingerprint,
+    jmdict: &JMDict,
+) -> std::io::Result<()> {
+    let mut cache_writer = BufWriter::new(File::create(path)?);
+    cache_writer.write_all(CACHE_MAGIC)?;
+    let header = CacheHeader {
+        version: CACHE_VERSION,
+        source: fingerprint.clone(),
+    };
+    bincode::serialize_into(&mut cache_writer, &header).map_err(bincode_to_io_error)?;
+    bincode::serialize_into(&mut cache_writer, jmdict.entities()).map_err(bincode_to_io_error)?;
+    bincode::serialize_into(&mut cache_writer, jmdict.entries()).map_err(bincode_to_io_error)?;
+    cache_writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const SAMPLE: &[u8] = include_bytes!("../tests/data/jmdict.xml");
+
+    /// Empty directory for the files of a test.
+    fn test_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("jmdict-cache-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn fingerprint_of(source_path: &Path) -> SourceFingerprint {
+        let source_data = std::fs::read(source_path).unwrap();
+        SourceFingerprint::new(&source_data, &std::fs::metadata(source_path).unwrap())
+    }
+
+    fn first_gloss(jmdict: &JMDict) -> String {
+        String::from(jmdict.entries()[0].senses()[0].glosses()[0].text())
+    }
+
+    #[test]
+    fn round_trip() {
+        let directory = test_directory("round-trip");
+        let source_path = directory.join("JMdict_e.xml");
+        let cache_path = directory.join("JMdict_e.cache");
+        std::fs::write(&source_path, SAMPLE).unwrap();
+        let parsed = load_jmdict_cached(&source_path, &cache_path).unwrap();
+        let cached = read_cache(&cache_path, &fingerprint_of(&source_path))
+            .unwrap()
+            .unwrap();
+        assert_eq!(cached.len(), parsed.len());
+        assert_eq!(first_gloss(&cached), "to eat");
+        assert_eq!(cached.entities(), parsed.entities());
+        let loaded = load_jmdict_cached(&source_path, &cache_path).unwrap();
+        assert_eq!(loaded.len(), parsed.len());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn invalidated_by_source_changes() {
+        let directory = test_directory("invalidation");
+        let source_path = directory.join("JMdict_e.xml");
+        let cache_path = directory.join("JMdict_e.cache");
+        std::fs::write(&source_path, SAMPLE).unwrap();
+        load_jmdict_cached(&source_path, &cache_path).unwrap();
+        let fingerprint = fingerprint_of(&source_path);
+        let other_size = SourceFingerprint {
+            size: fingerprint.size + 1,
+            ..fingerprint.clone()
+        };
+        let other_time = SourceFingerprint {
+            modified: Some((0, 0)),
+            ..fingerprint.clone()
+        };
+        let other_contents = SourceFingerprint {
+            hash: fingerprint.hash ^ 1,
+            ..fingerprint.clone()
+        };
+        for other in &[other_size, other_time, other_contents] {
+            assert!(read_cache(&cache_path, other).unwrap().is_none());
+        }
+        assert!(read_cache(&cache_path, &fingerprint).unwrap().is_some());
+        // same size, new contents
+        let changed = String::from_utf8_lossy(SAMPLE).replace("to eat", "to EAT");
+        std::fs::write(&source_path, changed).unwrap();
+        let reloaded = load_jmdict_cached(&source_path, &cache_path).unwrap();
+        assert_eq!(first_gloss(&reloaded), "to EAT");
+        let cached = read_cache(&cache_path, &fingerprint_of(&source_path))
+            .unwrap()
+            .unwrap();
+        assert_eq!(first_gloss(&cached), "to EAT");
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn invalidated_by_cache_version() {
+        let directory = test_directory("version");
+        let source_path = directory.join("JMdict_e.xml");
+        let cache_path = directory.join("JMdict_e.cache");
+        std::fs::write(&source_path, SAMPLE).unwrap();
+        load_jmdict_cached(&source_path, &cache_path).unwrap();
+        // the version follows the magic number, as a little-endian u32
+        let mut cache_data = std::fs::read(&cache_path).unwrap();
+        let version_bytes = (CACHE_VERSION - 1).to_le_bytes();
+        cache_data[CACHE_MAGIC.len()..CACHE_MAGIC.len() + 4].copy_from_slice(&version_bytes);
+        std::fs::write(&cache_path, cache_data).unwrap();
+        let fingerprint = fingerprint_of(&source_path);
+        assert!(read_cache(&cache_path, &fingerprint).unwrap().is_none());
+        load_jmdict_cached(&source_path, &cache_path).unwrap();
+        assert!(read_cache(&cache_path, &fingerprint).unwrap().is_some());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn unwritable_cache() {
+        let directory = test_directory("unwritable");
+        let source_path = directory.join("JMdict_e.xml");
+        std::fs::write(&source_path, SAMPLE).unwrap();
+        // a directory in place of the cache file cannot be replaced
+        let cache_path = directory.join("JMdict_e.cache");
+        std::fs::create_dir(&cache_path).unwrap();
+        let jmdict = load_jmdict_cached(&source_path, &cache_path).unwrap();
+        assert_eq!(first_gloss(&jmdict), "to eat");
+        assert!(!directory.join("JMdict_e.cache.tmp").exists());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
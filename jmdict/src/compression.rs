@@ -0,0 +1,71 @@
+//! Detection of the format of dictionary files from their first bytes, so that plain XML files
+//! and gzip, xz or zstd compressed files can be read alike.
+//!
+//! Reading xz files requires the `xz2` feature, and zstd files the `zstd` feature.
+
+use super::Error;
+use std::io::{BufRead, BufReader, Read};
+
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+const XZ_MAGIC: &[u8] = &[0xFD, 0x37, 0x7A, 0x58, 0x5A, 0x00];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+
+/// Compression format of a dictionary file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// Plain XML file
+    None,
+    Gzip,
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    /// Format of a file from its first bytes, plain XML if they match no known magic number.
+    pub fn detect(header: &[u8]) -> Self {
+        if header.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if header.starts_with(XZ_MAGIC) {
+            Compression::Xz
+        } else if header.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::None => "uncompressed",
+            Compression::Gzip => "gzip",
+            Compression::Xz => "xz",
+            Compression::Zstd => "zstd",
+        }
+    }
+    /// Optional feature of this crate needed to read files in this format.
+    pub fn feature(&self) -> Option<&'static str> {
+        match self {
+            Compression::None | Compression::Gzip => None,
+            Compression::Xz => Some("xz2"),
+            Compression::Zstd => Some("zstd"),
+        }
+    }
+}
+
+/// Decompressed contents of a dictionary file, its format being detected from its first bytes.
+pub fn decompress<'r, R: Read + 'r>(reader: R) -> Result<Box<dyn BufRead + 'r>, Error> {
+    let mut buf_reader = BufReader::new(reader);
+    let compression = Compression::detect(buf_reader.fill_buf()?);
+    let decompressed: Box<dyn BufRead + 'r> = match compression {
+        Compression::None => Box::new(buf_reader),
+        Compression::Gzip => Box::new(BufReader::new(flate2::bufread::GzDecoder::new(buf_reader))),
+        #[cfg(feature = "xz2")]
+        Compression::Xz => Box::new(BufReader::new(xz2::bufread::XzDecoder::new(buf_reader))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Box::new(BufReader::new(zstd::stream::read::Decoder::with_buffer(
+            buf_reader,
+        )?)),
+        #[allow(unreachable_patterns)]
+        _ => return Err(Error::UnsupportedCompression(compression)),
+    };
+    Ok(decompressed)
+}
@@ -0,0 +1,656 @@
+//! Conjugation tables of verbs and adjectives, built from their part of speech.
+
+use super::deinflect::GODAN_ROWS;
+use super::entry::JMDictEntry;
+use super::tags::PartOfSpeech;
+use serde::{Deserialize, Serialize};
+
+/// Form of a conjugation table row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum ConjugationForm {
+    NonPast,
+    Polite,
+    Past,
+    PolitePast,
+    Te,
+    Potential,
+    Passive,
+    Causative,
+    Volitional,
+    /// `-ba` conditional
+    Conditional,
+    Imperative,
+    /// Adverbial `-ku` form of adjectives
+    Adverbial,
+}
+
+impl ConjugationForm {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ConjugationForm::NonPast => "non-past",
+            ConjugationForm::Polite => "polite",
+            ConjugationForm::Past => "past",
+            ConjugationForm::PolitePast => "polite past",
+            ConjugationForm::Te => "te-form",
+            ConjugationForm::Potential => "potential",
+            ConjugationForm::Passive => "passive",
+            ConjugationForm::Causative => "causative",
+            ConjugationForm::Volitional => "volitional",
+            ConjugationForm::Conditional => "conditional",
+            ConjugationForm::Imperative => "imperative",
+            ConjugationForm::Adverbial => "adverbial",
+        }
+    }
+}
+
+/// Affirmative and negative conjugations of a word for one form.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ConjugationRow {
+    form: ConjugationForm,
+    affirmative: String,
+    negative: Option<String>,
+}
+
+impl ConjugationRow {
+    fn new(form: ConjugationForm, affirmative: String, negative: Option<String>) -> Self {
+        ConjugationRow {
+            form,
+            affirmative,
+            negative,
+        }
+    }
+    pub fn form(&self) -> ConjugationForm {
+        self.form
+    }
+    pub fn affirmative(&self) -> &str {
+        &self.affirmative
+    }
+    /// Negative conjugation, if the form has one.
+    pub fn negative(&self) -> Option<&str> {
+        self.negative.as_ref().map(|s| s as &str)
+    }
+}
+
+/// Standard conjugations of a verb or adjective.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ConjugationTable {
+    rows: Vec<ConjugationRow>,
+}
+
+impl ConjugationTable {
+    pub fn rows(&self) -> &[ConjugationRow] {
+        &self.rows
+    }
+    pub fn row(&self, form: ConjugationForm) -> Option<&ConjugationRow> {
+        self.rows.iter().find(|row| row.form == form)
+    }
+}
+
+/// Conjugated stems and forms of a verb, from which its table is built.
+struct VerbForms {
+    dictionary: String,
+    /// Stem before ない
+    negative: String,
+    /// Stem before ます
+    continuative: String,
+    te: String,
+    ta: String,
+    /// Potential form, `None` for verbs without one such as ある
+    potential: Option<String>,
+    passive: String,
+    causative: String,
+    volitional: String,
+    conditional: String,
+    imperative: String,
+}
+
+/// Negative of a verb conjugating like an ichidan verb, such as the potential form.
+fn ichidan_negative(verb: &str) -> String {
+    format!("{}ない", verb.trim_end_matches('る'))
+}
+
+impl VerbForms {
+    fn into_table(self) -> ConjugationTable {
+        use self::ConjugationForm::*;
+        let negative = |suffix: &str| Some(format!("{}な{}", self.negative, suffix));
+        let continuative = |suffix: &str| format!("{}{}", self.continuative, suffix);
+        let mut rows = vec![
+            ConjugationRow::new(NonPast, self.dictionary.clone(), negative("い")),
+            ConjugationRow::new(Polite, continuative("ます"), Some(continuative("ません"))),
+            ConjugationRow::new(Past, self.ta.clone(), negative("かった")),
+            ConjugationRow::new(
+                PolitePast,
+                continuative("ました"),
+                Some(continuative("ませんでした")),
+            ),
+            ConjugationRow::new(Te, self.te.clone(), negative("くて")),
+        ];
+        if let Some(potential) = &self.potential {
+            rows.push(ConjugationRow::new(
+                Potential,
+                potential.clone(),
+                Some(ichidan_negative(potential)),
+            ));
+        }
+        rows.extend(vec![
+            ConjugationRow::new(
+                Passive,
+                self.passive.clone(),
+                Some(ichidan_negative(&self.passive)),
+            ),
+            ConjugationRow::new(
+                Causative,
+                self.causative.clone(),
+                Some(ichidan_negative(&self.causative)),
+            ),
+            ConjugationRow::new(Volitional, self.volitional.clone(), None),
+            ConjugationRow::new(Conditional, self.conditional.clone(), negative("ければ")),
+            ConjugationRow::new(
+                Imperative,
+                self.imperative.clone(),
+                Some(format!("{}な", self.dictionary)),
+            ),
+        ]);
+        ConjugationTable { rows }
+    }
+}
+
+fn ichidan_forms(word: &str, part_of_speech: &PartOfSpeech) -> Option<VerbForms> {
+    let stem = word.strip_suffix("る")?;
+    let imperative = match part_of_speech {
+        // くれる: くれ instead of くれろ
+        PartOfSpeech::IchidanKureru => String::from(stem),
+        _ => format!("{}ろ", stem),
+    };
+    Some(VerbForms {
+        dictionary: String::from(word),
+        negative: String::from(stem),
+        continuative: String::from(stem),
+        te: format!("{}て", stem),
+        ta: format!("{}た", stem),
+        potential: Some(format!("{}られる", stem)),
+        passive: format!("{}られる", stem),
+        causative: format!("{}させる", stem),
+        volitional: format!("{}よう", stem),
+        conditional: format!("{}れば", stem),
+        imperative,
+    })
+}
+
+fn godan_forms(word: &str, part_of_speech: &PartOfSpeech) -> Option<VerbForms> {
+    let ending = word.chars().last()?;
+    let stem = &word[..word.len() - ending.len_utf8()];
+    let row = GODAN_ROWS.iter().find(|row| row[0].starts_with(ending))?;
+    let [_, a, i, e, o, te, ta] = *row;
+    let mut forms = VerbForms {
+        dictionary: String::from(word),
+        negative: format!("{}{}", stem, a),
+        continuative: format!("{}{}", stem, i),
+        te: format!("{}{}", stem, te),
+        ta: format!("{}{}", stem, ta),
+        potential: Some(format!("{}{}る", stem, e)),
+        passive: format!("{}{}れる", stem, a),
+        causative: format!("{}{}せる", stem, a),
+        volitional: format!("{}{}う", stem, o),
+        conditional: format!("{}{}ば", stem, e),
+        imperative: format!("{}{}", stem, e),
+    };
+    match part_of_speech {
+        // 行く: 行って, 行った
+        PartOfSpeech::GodanIku => {
+            forms.te = format!("{}って", stem);
+            forms.ta = format!("{}った", stem);
+        }
+        // ある, 有る or 在る: ない instead of あらない, and no potential form
+        PartOfSpeech::GodanRuIrregular => {
+            forms.negative = String::new();
+            forms.potential = None;
+        }
+        // honorific verbs such as いらっしゃる: いらっしゃいます, いらっしゃい
+        PartOfSpeech::GodanAru => {
+            forms.continuative = format!("{}い", stem);
+            forms.imperative = format!("{}い", stem);
+        }
+        // 問う: 問うて, 問うた
+        PartOfSpeech::GodanUSpecial => {
+            forms.te = format!("{}うて", stem);
+            forms.ta = format!("{}うた", stem);
+        }
+        _ => (),
+    }
+    Some(forms)
+}
+
+/// 来る, written in kana or kanji, possibly at the end of a compound verb.
+fn kuru_forms(word: &str) -> Option<VerbForms> {
+    let (prefix, kanji) = match word.strip_suffix("来る") {
+        Some(prefix) => (prefix, true),
+        None => (word.strip_suffix("くる")?, false),
+    };
+    let stem = |kana_stem: &str| {
+        if kanji {
+            format!("{}来", prefix)
+        } else {
+            format!("{}{}", prefix, kana_stem)
+        }
+    };
+    Some(VerbForms {
+        dictionary: String::from(word),
+        negative: stem("こ"),
+        continuative: stem("き"),
+        te: format!("{}て", stem("き")),
+        ta: format!("{}た", stem("き")),
+        potential: Some(format!("{}られる", stem("こ"))),
+        passive: format!("{}られる", stem("こ")),
+        causative: format!("{}させる", stem("こ")),
+        volitional: format!("{}よう", stem("こ")),
+        conditional: format!("{}れば", stem("く")),
+        imperative: format!("{}い", stem("こ")),
+    })
+}
+
+/// する, alone or after a noun such as 勉強.
+fn suru_forms(word: &str) -> VerbForms {
+    let prefix = word.strip_suffix("する").unwrap_or(word);
+    let form = |suffix: &str| format!("{}{}", prefix, suffix);
+    VerbForms {
+        dictionary: form("する"),
+        negative: form("し"),
+        continuative: form("し"),
+        te: form("して"),
+        ta: form("した"),
+        potential: Some(form("できる")),
+        passive: form("される"),
+        causative: form("させる"),
+        volitional: form("しよう"),
+        conditional: form("すれば"),
+        imperative: form("しろ"),
+    }
+}
+
+/// Verbs ending in する after a single kanji such as 愛する, conjugating partly like godan す
+/// verbs: 愛さない, 愛せる, 愛そう.
+fn suru_special_forms(word: &str) -> Option<VerbForms> {
+    let prefix = word.strip_suffix("する")?;
+    let form = |suffix: &str| format!("{}{}", prefix, suffix);
+    Some(VerbForms {
+        dictionary: String::from(word),
+        negative: form("さ"),
+        continuative: form("し"),
+        te: form("して"),
+        ta: form("した"),
+        potential: Some(form("せる")),
+        passive: form("される"),
+        causative: form("させる"),
+        volitional: form("そう"),
+        conditional: form("すれば"),
+        imperative: form("せよ"),
+    })
+}
+
+/// Conjugations of an i-adjective; いい conjugates from よい.
+fn adjective_table(word: &str, part_of_speech: &PartOfSpeech) -> Option<ConjugationTable> {
+    use self::ConjugationForm::*;
+    let stem = match part_of_speech {
+        PartOfSpeech::AdjectiveIx => format!("{}よ", word.strip_suffix("いい")?),
+        _ => String::from(word.strip_suffix("い")?),
+    };
+    let form = |suffix: &str| format!("{}{}", stem, suffix);
+    let negative = |suffix: &str| Some(format!("{}くな{}", stem, suffix));
+    let rows = vec![
+        ConjugationRow::new(NonPast, String::from(word), negative("い")),
+        ConjugationRow::new(Polite, format!("{}です", word), negative("いです")),
+        ConjugationRow::new(Past, form("かった"), negative("かった")),
+        ConjugationRow::new(PolitePast, form("かったです"), negative("かったです")),
+        ConjugationRow::new(Te, form("くて"), negative("くて")),
+        ConjugationRow::new(Adverbial, form("く"), None),
+        ConjugationRow::new(Conditional, form("ければ"), negative("ければ")),
+    ];
+    Some(ConjugationTable { rows })
+}
+
+/// Conjugation table of a word in dictionary form, if its part of speech conjugates.
+///
+/// Nouns taking する, such as 勉強, are conjugated with する.
+pub fn conjugate(word: &str, part_of_speech: &PartOfSpeech) -> Option<ConjugationTable> {
+    let verb_forms = match part_of_speech {
+        PartOfSpeech::Ichidan | PartOfSpeech::IchidanKureru => ichidan_forms(word, part_of_speech),
+        PartOfSpeech::GodanAru
+        | PartOfSpeech::GodanBu
+        | PartOfSpeech::GodanGu
+        | PartOfSpeech::GodanKu
+        | PartOfSpeech::GodanIku
+        | PartOfSpeech::GodanMu
+        | PartOfSpeech::GodanNu
+        | PartOfSpeech::GodanRu
+        | PartOfSpeech::GodanRuIrregular
+        | PartOfSpeech::GodanSu
+        | PartOfSpeech::GodanTsu
+        | PartOfSpeech::GodanU
+        | PartOfSpeech::GodanUSpecial
+        | PartOfSpeech::GodanUru => godan_forms(word, part_of_speech),
+        PartOfSpeech::Kuru => kuru_forms(word),
+        PartOfSpeech::Suru | PartOfSpeech::SuruIncluded => Some(suru_forms(word)),
+        PartOfSpeech::SuruSpecial => suru_special_forms(word),
+        PartOfSpeech::AdjectiveI | PartOfSpeech::AdjectiveIx => {
+            return adjective_table(word, part_of_speech)
+        }
+        _ => None,
+    };
+    verb_forms.map(VerbForms::into_table)
+}
+
+/// First part of speech of an entry that has a conjugation table.
+pub fn conjugating_part_of_speech(entry: &JMDictEntry) -> Option<&PartOfSpeech> {
+    entry
+        .senses()
+        .iter()
+        .flat_map(|sense| sense.parts_of_speech())
+        .find(|part_of_speech| conjugates(part_of_speech))
+}
+
+/// Whether words of a part of speech have a conjugation table.
+pub fn conjugates(part_of_speech: &PartOfSpeech) -> bool {
+    matches!(
+        part_of_speech,
+        PartOfSpeech::Ichidan
+            | PartOfSpeech::IchidanKureru
+            | PartOfSpeech::GodanAru
+            | PartOfSpeech::GodanBu
+            | PartOfSpeech::GodanGu
+            | PartOfSpeech::GodanKu
+            | PartOfSpeech::GodanIku
+            | PartOfSpeech::GodanMu
+            | PartOfSpeech::GodanNu
+            | PartOfSpeech::GodanRu
+            | PartOfSpeech::GodanRuIrregular
+            | PartOfSpeech::GodanSu
+            | PartOfSpeech::GodanTsu
+            | PartOfSpeech::GodanU
+            | PartOfSpeech::GodanUSpecial
+            | PartOfSpeech::GodanUru
+            | PartOfSpeech::Kuru
+            | PartOfSpeech::Suru
+            | PartOfSpeech::SuruSpecial
+            | PartOfSpeech::SuruIncluded
+            | PartOfSpeech::AdjectiveI
+            | PartOfSpeech::AdjectiveIx
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConjugationForm::*;
+    use super::*;
+
+    /// Rows of the table of a word as `(form, affirmative, negative)`.
+    fn table(
+        word: &str,
+        part_of_speech: PartOfSpeech,
+    ) -> Vec<(ConjugationForm, String, Option<String>)> {
+        conjugate(word, &part_of_speech)
+            .unwrap()
+            .rows()
+            .iter()
+            .map(|row| {
+                (
+                    row.form(),
+                    String::from(row.affirmative()),
+                    row.negative().map(String::from),
+                )
+            })
+            .collect()
+    }
+
+    fn rows(
+        rows: &[(ConjugationForm, &str, Option<&str>)],
+    ) -> Vec<(ConjugationForm, String, Option<String>)> {
+        rows.iter()
+            .map(|&(form, affirmative, negative)| {
+                (form, String::from(affirmative), negative.map(String::from))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ichidan() {
+        assert_eq!(
+            table("食べる", PartOfSpeech::Ichidan),
+            rows(&[
+                (NonPast, "食べる", Some("食べない")),
+                (Polite, "食べます", Some("食べません")),
+                (Past, "食べた", Some("食べなかった")),
+                (PolitePast, "食べました", Some("食べませんでした")),
+                (Te, "食べて", Some("食べなくて")),
+                (Potential, "食べられる", Some("食べられない")),
+                (Passive, "食べられる", Some("食べられない")),
+                (Causative, "食べさせる", Some("食べさせない")),
+                (Volitional, "食べよう", None),
+                (Conditional, "食べれば", Some("食べなければ")),
+                (Imperative, "食べろ", Some("食べるな")),
+            ])
+        );
+    }
+
+    #[test]
+    fn ichidan_kureru() {
+        assert_eq!(
+            table("くれる", PartOfSpeech::IchidanKureru),
+            rows(&[
+                (NonPast, "くれる", Some("くれない")),
+                (Polite, "くれます", Some("くれません")),
+                (Past, "くれた", Some("くれなかった")),
+                (PolitePast, "くれました", Some("くれませんでした")),
+                (Te, "くれて", Some("くれなくて")),
+                (Potential, "くれられる", Some("くれられない")),
+                (Passive, "くれられる", Some("くれられない")),
+                (Causative, "くれさせる", Some("くれさせない")),
+                (Volitional, "くれよう", None),
+                (Conditional, "くれれば", Some("くれなければ")),
+                (Imperative, "くれ", Some("くれるな")),
+            ])
+        );
+    }
+
+    #[test]
+    fn godan() {
+        assert_eq!(
+            table("書く", PartOfSpeech::GodanKu),
+            rows(&[
+                (NonPast, "書く", Some("書かない")),
+                (Polite, "書きます", Some("書きません")),
+                (Past, "書いた", Some("書かなかった")),
+                (PolitePast, "書きました", Some("書きませんでした")),
+                (Te, "書いて", Some("書かなくて")),
+                (Potential, "書ける", Some("書けない")),
+                (Passive, "書かれる", Some("書かれない")),
+                (Causative, "書かせる", Some("書かせない")),
+                (Volitional, "書こう", None),
+                (Conditional, "書けば", Some("書かなければ")),
+                (Imperative, "書け", Some("書くな")),
+            ])
+        );
+    }
+
+    #[test]
+    fn godan_iku() {
+        assert_eq!(
+            table("行く", PartOfSpeech::GodanIku),
+            rows(&[
+                (NonPast, "行く", Some("行かない")),
+                (Polite, "行きます", Some("行きません")),
+                (Past, "行った", Some("行かなかった")),
+                (PolitePast, "行きました", Some("行きませんでした")),
+                (Te, "行って", Some("行かなくて")),
+                (Potential, "行ける", Some("行けない")),
+                (Passive, "行かれる", Some("行かれない")),
+                (Causative, "行かせる", Some("行かせない")),
+                (Volitional, "行こう", None),
+                (Conditional, "行けば", Some("行かなければ")),
+                (Imperative, "行け", Some("行くな")),
+            ])
+        );
+    }
+
+    #[test]
+    fn godan_aru_has_no_potential() {
+        let conjugations = table("ある", PartOfSpeech::GodanRuIrregular);
+        assert_eq!(
+            conjugations[0],
+            (NonPast, String::from("ある"), Some(String::from("ない")))
+        );
+        assert_eq!(
+            conjugations[2],
+            (Past, String::from("あった"), Some(String::from("なかった")))
+        );
+        assert!(conjugations.iter().all(|&(form, _, _)| form != Potential));
+    }
+
+    #[test]
+    fn godan_aru_in_kanji() {
+        for &(word, past) in &[("有る", "有った"), ("在る", "在った")] {
+            let conjugations = table(word, PartOfSpeech::GodanRuIrregular);
+            assert_eq!(
+                conjugations[0],
+                (NonPast, String::from(word), Some(String::from("ない")))
+            );
+            assert_eq!(
+                conjugations[2],
+                (Past, String::from(past), Some(String::from("なかった")))
+            );
+        }
+    }
+
+    #[test]
+    fn suru() {
+        assert_eq!(
+            table("する", PartOfSpeech::SuruIncluded),
+            rows(&[
+                (NonPast, "する", Some("しない")),
+                (Polite, "します", Some("しません")),
+                (Past, "した", Some("しなかった")),
+                (PolitePast, "しました", Some("しませんでした")),
+                (Te, "して", Some("しなくて")),
+                (Potential, "できる", Some("できない")),
+                (Passive, "される", Some("されない")),
+                (Causative, "させる", Some("させない")),
+                (Volitional, "しよう", None),
+                (Conditional, "すれば", Some("しなければ")),
+                (Imperative, "しろ", Some("するな")),
+            ])
+        );
+    }
+
+    #[test]
+    fn suru_noun() {
+        assert_eq!(
+            table("勉強", PartOfSpeech::Suru),
+            rows(&[
+                (NonPast, "勉強する", Some("勉強しない")),
+                (Polite, "勉強します", Some("勉強しません")),
+                (Past, "勉強した", Some("勉強しなかった")),
+                (PolitePast, "勉強しました", Some("勉強しませんでした")),
+                (Te, "勉強して", Some("勉強しなくて")),
+                (Potential, "勉強できる", Some("勉強できない")),
+                (Passive, "勉強される", Some("勉強されない")),
+                (Causative, "勉強させる", Some("勉強させない")),
+                (Volitional, "勉強しよう", None),
+                (Conditional, "勉強すれば", Some("勉強しなければ")),
+                (Imperative, "勉強しろ", Some("勉強するな")),
+            ])
+        );
+    }
+
+    #[test]
+    fn suru_special() {
+        assert_eq!(
+            table("愛する", PartOfSpeech::SuruSpecial),
+            rows(&[
+                (NonPast, "愛する", Some("愛さない")),
+                (Polite, "愛します", Some("愛しません")),
+                (Past, "愛した", Some("愛さなかった")),
+                (PolitePast, "愛しました", Some("愛しませんでした")),
+                (Te, "愛して", Some("愛さなくて")),
+                (Potential, "愛せる", Some("愛せない")),
+                (Passive, "愛される", Some("愛されない")),
+                (Causative, "愛させる", Some("愛させない")),
+                (Volitional, "愛そう", None),
+                (Conditional, "愛すれば", Some("愛さなければ")),
+                (Imperative, "愛せよ", Some("愛するな")),
+            ])
+        );
+    }
+
+    #[test]
+    fn kuru() {
+        assert_eq!(
+            table("来る", PartOfSpeech::Kuru),
+            rows(&[
+                (NonPast, "来る", Some("来ない")),
+                (Polite, "来ます", Some("来ません")),
+                (Past, "来た", Some("来なかった")),
+                (PolitePast, "来ました", Some("来ませんでした")),
+                (Te, "来て", Some("来なくて")),
+                (Potential, "来られる", Some("来られない")),
+                (Passive, "来られる", Some("来られない")),
+                (Causative, "来させる", Some("来させない")),
+                (Volitional, "来よう", None),
+                (Conditional, "来れば", Some("来なければ")),
+                (Imperative, "来い", Some("来るな")),
+            ])
+        );
+        assert_eq!(
+            table("くる", PartOfSpeech::Kuru),
+            rows(&[
+                (NonPast, "くる", Some("こない")),
+                (Polite, "きます", Some("きません")),
+                (Past, "きた", Some("こなかった")),
+                (PolitePast, "きました", Some("きませんでした")),
+                (Te, "きて", Some("こなくて")),
+                (Potential, "こられる", Some("こられない")),
+                (Passive, "こられる", Some("こられない")),
+                (Causative, "こさせる", Some("こさせない")),
+                (Volitional, "こよう", None),
+                (Conditional, "くれば", Some("こなければ")),
+                (Imperative, "こい", Some("くるな")),
+            ])
+        );
+    }
+
+    #[test]
+    fn adjective() {
+        assert_eq!(
+            table("高い", PartOfSpeech::AdjectiveI),
+            rows(&[
+                (NonPast, "高い", Some("高くない")),
+                (Polite, "高いです", Some("高くないです")),
+                (Past, "高かった", Some("高くなかった")),
+                (PolitePast, "高かったです", Some("高くなかったです")),
+                (Te, "高くて", Some("高くなくて")),
+                (Adverbial, "高く", None),
+                (Conditional, "高ければ", Some("高くなければ")),
+            ])
+        );
+    }
+
+    #[test]
+    fn adjective_ii() {
+        assert_eq!(
+            table("いい", PartOfSpeech::AdjectiveIx),
+            rows(&[
+                (NonPast, "いい", Some("よくない")),
+                (Polite, "いいです", Some("よくないです")),
+                (Past, "よかった", Some("よくなかった")),
+                (PolitePast, "よかったです", Some("よくなかったです")),
+                (Te, "よくて", Some("よくなくて")),
+                (Adverbial, "よく", None),
+                (Conditional, "よければ", Some("よくなければ")),
+            ])
+        );
+    }
+
+    #[test]
+    fn non_conjugating() {
+        assert!(conjugate("本", &PartOfSpeech::Noun).is_none());
+    }
+}
@@ -0,0 +1,606 @@
+//! Deinflection of conjugated verbs and adjectives back to their dictionary form.
+//!
+//! Conjugated endings are replaced by dictionary endings according to rules grouped by verb and
+//! adjective class, repeatedly, so that chained conjugations such as 食べませんでした are undone
+//! step by step. Candidates only make sense for entries with a matching part of speech, which
+//! [`Deinflection::matches_entry`](struct.Deinflection.html#method.matches_entry) checks.
+
+use super::entry::JMDictEntry;
+use super::tags::PartOfSpeech;
+use serde::{Deserialize, Serialize};
+
+/// Conjugation recognized while deinflecting a word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Inflection {
+    Polite,
+    Negative,
+    Past,
+    Te,
+    Progressive,
+    Potential,
+    Passive,
+    /// Ichidan verbs and 来る share their potential and passive forms.
+    PotentialOrPassive,
+    Causative,
+    Volitional,
+    /// `-ba` conditional
+    Conditional,
+    /// `-tara` conditional
+    Tara,
+    Imperative,
+    /// `-tai` form
+    Desire,
+    /// Adverbial `-ku` form of adjectives
+    Adverbial,
+}
+
+impl Inflection {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Inflection::Polite => "polite",
+            Inflection::Negative => "negative",
+            Inflection::Past => "past",
+            Inflection::Te => "te-form",
+            Inflection::Progressive => "progressive",
+            Inflection::Potential => "potential",
+            Inflection::Passive => "passive",
+            Inflection::PotentialOrPassive => "potential or passive",
+            Inflection::Causative => "causative",
+            Inflection::Volitional => "volitional",
+            Inflection::Conditional => "conditional",
+            Inflection::Tara => "-tara conditional",
+            Inflection::Imperative => "imperative",
+            Inflection::Desire => "-tai form",
+            Inflection::Adverbial => "adverbial",
+        }
+    }
+}
+
+/// Describe a sequence of inflections, such as "polite negative past".
+pub fn describe_inflections(inflections: &[Inflection]) -> String {
+    let descriptions: Vec<&str> = inflections.iter().map(Inflection::description).collect();
+    descriptions.join(" ")
+}
+
+// Word classes of deinflection candidates, as bit flags.
+const CLASS_ICHIDAN: u16 = 1;
+const CLASS_GODAN: u16 = 1 << 1;
+const CLASS_GODAN_IKU: u16 = 1 << 2;
+const CLASS_KURU: u16 = 1 << 3;
+const CLASS_SURU: u16 = 1 << 4;
+const CLASS_ADJECTIVE_I: u16 = 1 << 5;
+/// Polite `-masu` form, conjugated further
+const CLASS_MASU: u16 = 1 << 6;
+/// `-te` form, followed by an auxiliary verb
+const CLASS_TE: u16 = 1 << 7;
+/// Form ending a word, which cannot be conjugated further
+const CLASS_TERMINAL: u16 = 1 << 8;
+/// Classes a word as typed by the user may have
+const CLASS_ANY: u16 = 0xFFFF;
+
+/// Class of the dictionary form of a part of speech, if it conjugates.
+fn part_of_speech_class(part_of_speech: &PartOfSpeech) -> u16 {
+    match part_of_speech {
+        PartOfSpeech::Ichidan | PartOfSpeech::IchidanKureru => CLASS_ICHIDAN,
+        PartOfSpeech::GodanIku => CLASS_GODAN | CLASS_GODAN_IKU,
+        PartOfSpeech::GodanAru
+        | PartOfSpeech::GodanBu
+        | PartOfSpeech::GodanGu
+        | PartOfSpeech::GodanKu
+        | PartOfSpeech::GodanMu
+        | PartOfSpeech::GodanNu
+        | PartOfSpeech::GodanRu
+        | PartOfSpeech::GodanRuIrregular
+        | PartOfSpeech::GodanSu
+        | PartOfSpeech::GodanTsu
+        | PartOfSpeech::GodanU
+        | PartOfSpeech::GodanUSpecial
+        | PartOfSpeech::GodanUru => CLASS_GODAN,
+        PartOfSpeech::Kuru => CLASS_KURU,
+        PartOfSpeech::Suru | PartOfSpeech::SuruSpecial | PartOfSpeech::SuruIncluded => CLASS_SURU,
+        PartOfSpeech::AdjectiveI | PartOfSpeech::AdjectiveIx => CLASS_ADJECTIVE_I,
+        _ => 0,
+    }
+}
+
+/// Replacement of a conjugated ending by a less conjugated one.
+#[derive(Debug, Clone)]
+struct DeinflectionRule {
+    inflected: String,
+    base: String,
+    /// Classes the conjugated word must have
+    classes_in: u16,
+    /// Class of the word after the ending is replaced
+    class_out: u16,
+    inflections: &'static [Inflection],
+}
+
+/// Conjugation stems of a verb class, each inserted before the conjugation suffix.
+struct VerbStems {
+    class: u16,
+    dictionary: &'static str,
+    negative: &'static str,
+    continuative: &'static str,
+    te: &'static str,
+    ta: &'static str,
+    potential: &'static str,
+    passive: &'static str,
+    causative: &'static str,
+    volitional: &'static str,
+    conditional: &'static str,
+    imperative: &'static str,
+}
+
+/// Godan endings: dictionary, a-row, i-row, e-row, o-row, te-form and past.
+#[rustfmt::skip]
+pub(crate) const GODAN_ROWS: &[[&str; 7]] = &[
+    ["う", "わ", "い", "え", "お", "って", "った"],
+    ["く", "か", "き", "け", "こ", "いて", "いた"],
+    ["ぐ", "が", "ぎ", "げ", "ご", "いで", "いだ"],
+    ["す", "さ", "し", "せ", "そ", "して", "した"],
+    ["つ", "た", "ち", "て", "と", "って", "った"],
+    ["ぬ", "な", "に", "ね", "の", "んで", "んだ"],
+    ["ぶ", "ば", "び", "べ", "ぼ", "んで", "んだ"],
+    ["む", "ま", "み", "め", "も", "んで", "んだ"],
+    ["る", "ら", "り", "れ", "ろ", "って", "った"],
+];
+
+impl DeinflectionRule {
+    fn new(
+        inflected: String,
+        base: &str,
+        classes_in: u16,
+        class_out: u16,
+        inflections: &'static [Inflection],
+    ) -> Self {
+        DeinflectionRule {
+            inflected,
+            base: String::from(base),
+            classes_in,
+            class_out,
+            inflections,
+        }
+    }
+}
+
+fn verb_rules(stems: &VerbStems, rules: &mut Vec<DeinflectionRule>) {
+    use self::Inflection::*;
+    let base = stems.dictionary;
+    let class = stems.class;
+    let mut add = |inflected: String, classes_in: u16, inflections: &'static [Inflection]| {
+        rules.push(DeinflectionRule::new(
+            inflected,
+            base,
+            classes_in,
+            class,
+            inflections,
+        ))
+    };
+    add(
+        format!("{}ない", stems.negative),
+        CLASS_ADJECTIVE_I,
+        &[Negative],
+    );
+    add(format!("{}ます", stems.continuative), CLASS_MASU, &[Polite]);
+    add(
+        format!("{}たい", stems.continuative),
+        CLASS_ADJECTIVE_I,
+        &[Desire],
+    );
+    add(String::from(stems.te), CLASS_TE | CLASS_TERMINAL, &[Te]);
+    add(String::from(stems.ta), CLASS_TERMINAL, &[Past]);
+    add(format!("{}ら", stems.ta), CLASS_TERMINAL, &[Tara]);
+    if stems.potential == stems.passive {
+        add(
+            String::from(stems.potential),
+            CLASS_ICHIDAN,
+            &[PotentialOrPassive],
+        );
+    } else {
+        add(String::from(stems.potential), CLASS_ICHIDAN, &[Potential]);
+        add(String::from(stems.passive), CLASS_ICHIDAN, &[Passive]);
+    }
+    add(String::from(stems.causative), CLASS_ICHIDAN, &[Causative]);
+    add(
+        String::from(stems.volitional),
+        CLASS_TERMINAL,
+        &[Volitional],
+    );
+    add(
+        String::from(stems.conditional),
+        CLASS_TERMINAL,
+        &[Conditional],
+    );
+    add(
+        String::from(stems.imperative),
+        CLASS_TERMINAL,
+        &[Imperative],
+    );
+}
+
+fn deinflection_rules() -> Vec<DeinflectionRule> {
+    use self::Inflection::*;
+    let mut rules: Vec<DeinflectionRule> = Vec::new();
+    // ichidan verbs
+    verb_rules(
+        &VerbStems {
+            class: CLASS_ICHIDAN,
+            dictionary: "る",
+            negative: "",
+            continuative: "",
+            te: "て",
+            ta: "た",
+            potential: "られる",
+            passive: "られる",
+            causative: "させる",
+            volitional: "よう",
+            conditional: "れば",
+            imperative: "ろ",
+        },
+        &mut rules,
+    );
+    // godan verbs, with forms built from their rows
+    for row in GODAN_ROWS {
+        let [dictionary, a, i, e, o, te, ta] = *row;
+        let mut add = |inflected: String, classes_in: u16, inflections: &'static [Inflection]| {
+            rules.push(DeinflectionRule::new(
+                inflected,
+                dictionary,
+                classes_in,
+                CLASS_GODAN,
+                inflections,
+            ))
+        };
+        add(format!("{}ない", a), CLASS_ADJECTIVE_I, &[Negative]);
+        add(format!("{}ます", i), CLASS_MASU, &[Polite]);
+        add(format!("{}たい", i), CLASS_ADJECTIVE_I, &[Desire]);
+        add(String::from(te), CLASS_TE | CLASS_TERMINAL, &[Te]);
+        add(String::from(ta), CLASS_TERMINAL, &[Past]);
+        add(format!("{}ら", ta), CLASS_TERMINAL, &[Tara]);
+        add(format!("{}る", e), CLASS_ICHIDAN, &[Potential]);
+        add(format!("{}れる", a), CLASS_ICHIDAN, &[Passive]);
+        add(format!("{}せる", a), CLASS_ICHIDAN, &[Causative]);
+        add(format!("{}う", o), CLASS_TERMINAL, &[Volitional]);
+        add(format!("{}ば", e), CLASS_TERMINAL, &[Conditional]);
+        add(String::from(e), CLASS_TERMINAL, &[Imperative]);
+    }
+    // 行く and its compounds: 行って, 行った
+    for &(inflected, inflections) in &[
+        ("って", &[Te] as &'static [Inflection]),
+        ("った", &[Past]),
+        ("ったら", &[Tara]),
+    ] {
+        rules.push(DeinflectionRule::new(
+            String::from(inflected),
+            "く",
+            CLASS_TE | CLASS_TERMINAL,
+            CLASS_GODAN_IKU,
+            inflections,
+        ));
+    }
+    // 来る, in kana and in kanji
+    verb_rules(
+        &VerbStems {
+            class: CLASS_KURU,
+            dictionary: "くる",
+            negative: "こ",
+            continuative: "き",
+            te: "きて",
+            ta: "きた",
+            potential: "こられる",
+            passive: "こられる",
+            causative: "こさせる",
+            volitional: "こよう",
+            conditional: "くれば",
+            imperative: "こい",
+        },
+        &mut rules,
+    );
+    verb_rules(
+        &VerbStems {
+            class: CLASS_KURU,
+            dictionary: "来る",
+            negative: "来",
+            continuative: "来",
+            te: "来て",
+            ta: "来た",
+            potential: "来られる",
+            passive: "来られる",
+            causative: "来させる",
+            volitional: "来よう",
+            conditional: "来れば",
+            imperative: "来い",
+        },
+        &mut rules,
+    );
+    // する, alone or after a noun
+    verb_rules(
+        &VerbStems {
+            class: CLASS_SURU,
+            dictionary: "する",
+            negative: "し",
+            continuative: "し",
+            te: "して",
+            ta: "した",
+            potential: "できる",
+            passive: "される",
+            causative: "させる",
+            volitional: "しよう",
+            conditional: "すれば",
+            imperative: "しろ",
+        },
+        &mut rules,
+    );
+    // conjugations of the polite -masu ending
+    for &(inflected, inflections) in &[
+        ("ました", &[Past] as &'static [Inflection]),
+        ("ません", &[Negative]),
+        ("ませんでした", &[Negative, Past]),
+        ("ましょう", &[Volitional]),
+        ("まして", &[Te]),
+    ] {
+        rules.push(DeinflectionRule::new(
+            String::from(inflected),
+            "ます",
+            CLASS_TERMINAL,
+            CLASS_MASU,
+            inflections,
+        ));
+    }
+    // progressive -te iru, and its contracted -te ru form
+    for &(inflected, base) in &[
+        ("ている", "て"),
+        ("でいる", "で"),
+        ("てる", "て"),
+        ("でる", "で"),
+    ] {
+        rules.push(DeinflectionRule::new(
+            String::from(inflected),
+            base,
+            CLASS_ICHIDAN,
+            CLASS_TE,
+            &[Progressive],
+        ));
+    }
+    // i-adjectives, including the negative ない ending of verbs
+    for &(inflected, classes_in, inflections) in &[
+        ("かった", CLASS_TERMINAL, &[Past] as &'static [Inflection]),
+        ("かったら", CLASS_TERMINAL, &[Tara]),
+        ("くない", CLASS_ADJECTIVE_I, &[Negative]),
+        ("くて", CLASS_TE | CLASS_TERMINAL, &[Te]),
+        ("く", CLASS_TERMINAL, &[Adverbial]),
+        ("ければ", CLASS_TERMINAL, &[Conditional]),
+    ] {
+        rules.push(DeinflectionRule::new(
+            String::from(inflected),
+            "い",
+            classes_in,
+            CLASS_ADJECTIVE_I,
+            inflections,
+        ));
+    }
+    rules
+}
+
+/// Maximum number of conjugations undone in a chain.
+const MAX_DEINFLECTION_DEPTH: usize = 8;
+
+/// Candidate dictionary form of a conjugated word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deinflection {
+    word: String,
+    classes: u16,
+    inflections: Vec<Inflection>,
+}
+
+impl Deinflection {
+    /// Candidate word.
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+    /// Conjugations undone to get the candidate, from the innermost to the outermost.
+    pub fn inflections(&self) -> &[Inflection] {
+        &self.inflections
+    }
+    /// Whether the candidate is the word itself, without any conjugation undone.
+    pub fn is_original(&self) -> bool {
+        self.inflections.is_empty()
+    }
+    /// Whether a part of speech allows the conjugations of the candidate.
+    pub fn matches_part_of_speech(&self, part_of_speech: &PartOfSpeech) -> bool {
+        self.is_original() || self.classes & part_of_speech_class(part_of_speech) != 0
+    }
+    /// Whether a dictionary entry may be the dictionary form of the candidate.
+    pub fn matches_entry(&self, entry: &JMDictEntry) -> bool {
+        self.is_original()
+            || entry
+                .senses()
+                .iter()
+                .flat_map(|sense| sense.parts_of_speech())
+                .any(|part_of_speech| self.matches_part_of_speech(part_of_speech))
+    }
+    /// For a candidate ending with する, the noun taking する, such as 勉強 for 勉強する.
+    pub fn suru_noun(&self) -> Option<&str> {
+        if self.classes & CLASS_SURU == 0 || self.is_original() {
+            return None;
+        }
+        let noun = self.word.trim_end_matches("する");
+        if noun.len() < self.word.len() && !noun.is_empty() {
+            Some(noun)
+        } else {
+            None
+        }
+    }
+}
+
+/// Candidate dictionary forms of a word, with the conjugations undone to get each of them.
+///
+/// The word itself is the first candidate; the others come in order of deinflection depth.
+pub fn deinflect(word: &str) -> Vec<Deinflection> {
+    lazy_static! {
+        static ref RULES: Vec<DeinflectionRule> = deinflection_rules();
+    }
+    let mut candidates = vec![Deinflection {
+        word: String::from(word),
+        classes: CLASS_ANY,
+        inflections: Vec::new(),
+    }];
+    let mut depth_start = 0usize;
+    for _ in 0..MAX_DEINFLECTION_DEPTH {
+        let depth_end = candidates.len();
+        for candidate_index in depth_start..depth_end {
+            for rule in RULES.iter() {
+                let candidate = &candidates[candidate_index];
+                if candidate.classes & rule.classes_in == 0
+                    || !candidate.word.ends_with(rule.inflected.as_str())
+                {
+                    continue;
+                }
+                let stem = &candidate.word[..candidate.word.len() - rule.inflected.len()];
+                let new_word = format!("{}{}", stem, rule.base);
+                if candidates
+                    .iter()
+                    .any(|c| c.word == new_word && c.classes == rule.class_out)
+                {
+                    continue;
+                }
+                let mut inflections = rule.inflections.to_vec();
+                inflections.extend_from_slice(&candidate.inflections);
+                candidates.push(Deinflection {
+                    word: new_word,
+                    classes: rule.class_out,
+                    inflections,
+                });
+            }
+        }
+        if depth_end == candidates.len() {
+            break;
+        }
+        depth_start = depth_end;
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Inflection::*;
+    use super::*;
+
+    /// Conjugations undone to get a dictionary form of a given part of speech, if it is one of
+    /// the candidates.
+    fn inflections(
+        word: &str,
+        dictionary_form: &str,
+        part_of_speech: PartOfSpeech,
+    ) -> Option<Vec<Inflection>> {
+        deinflect(word)
+            .into_iter()
+            .find(|deinflection| {
+                !deinflection.is_original()
+                    && deinflection.word() == dictionary_form
+                    && deinflection.matches_part_of_speech(&part_of_speech)
+            })
+            .map(|deinflection| deinflection.inflections().to_vec())
+    }
+
+    #[test]
+    fn original_first() {
+        let candidates = deinflect("食べました");
+        assert_eq!(candidates[0].word(), "食べました");
+        assert!(candidates[0].is_original());
+        assert!(candidates[0].matches_part_of_speech(&PartOfSpeech::Noun));
+    }
+
+    #[test]
+    fn ichidan() {
+        assert_eq!(
+            inflections("食べました", "食べる", PartOfSpeech::Ichidan),
+            Some(vec![Polite, Past])
+        );
+        assert_eq!(
+            inflections("食べませんでした", "食べる", PartOfSpeech::Ichidan),
+            Some(vec![Polite, Negative, Past])
+        );
+        assert_eq!(
+            inflections("食べられる", "食べる", PartOfSpeech::Ichidan),
+            Some(vec![PotentialOrPassive])
+        );
+        assert_eq!(
+            inflections("食べている", "食べる", PartOfSpeech::Ichidan),
+            Some(vec![Te, Progressive])
+        );
+    }
+
+    #[test]
+    fn godan() {
+        assert_eq!(
+            inflections("行った", "行く", PartOfSpeech::GodanIku),
+            Some(vec![Past])
+        );
+        assert_eq!(
+            inflections("書いた", "書く", PartOfSpeech::GodanKu),
+            Some(vec![Past])
+        );
+        assert_eq!(
+            inflections("書かなかった", "書く", PartOfSpeech::GodanKu),
+            Some(vec![Negative, Past])
+        );
+        // 行った is not the past of a regular godan く verb
+        assert_eq!(inflections("行った", "行く", PartOfSpeech::GodanKu), None);
+    }
+
+    #[test]
+    fn adjective() {
+        assert_eq!(
+            inflections("高くなかった", "高い", PartOfSpeech::AdjectiveI),
+            Some(vec![Negative, Past])
+        );
+        assert_eq!(
+            inflections("高かった", "高い", PartOfSpeech::AdjectiveI),
+            Some(vec![Past])
+        );
+        assert_eq!(inflections("高かった", "高い", PartOfSpeech::Noun), None);
+    }
+
+    #[test]
+    fn suru() {
+        assert_eq!(
+            inflections("勉強しました", "勉強する", PartOfSpeech::Suru),
+            Some(vec![Polite, Past])
+        );
+        let candidate = deinflect("勉強しました")
+            .into_iter()
+            .find(|deinflection| deinflection.word() == "勉強する")
+            .unwrap();
+        assert_eq!(candidate.suru_noun(), Some("勉強"));
+        let candidate = deinflect("しました")
+            .into_iter()
+            .find(|deinflection| deinflection.word() == "する")
+            .unwrap();
+        assert_eq!(candidate.suru_noun(), None);
+    }
+
+    #[test]
+    fn not_inflected() {
+        assert_eq!(
+            deinflect("学生")
+                .into_iter()
+                .map(|d| d.word)
+                .collect::<Vec<String>>(),
+            vec![String::from("学生")]
+        );
+        // looks like the te-form of a verb
+        assert!(deinflect("きって")
+            .iter()
+            .filter(|deinflection| !deinflection.is_original())
+            .all(|deinflection| !deinflection.matches_part_of_speech(&PartOfSpeech::Noun)));
+    }
+
+    #[test]
+    fn describe() {
+        assert_eq!(
+            describe_inflections(&[Polite, Negative, Past]),
+            "polite negative past"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use super::deinflect::{self, Inflection};
+use super::entry::JMDictEntry;
+use super::entry_id::JMDictEntryId;
+use super::kana;
+use super::search::{FormIndex, GlossIndex, WordPattern};
+use super::tags::Entities;
+use std::collections::HashMap;
+
+/// Parsed JMdict dictionary kept in memory, indexed by kanji, reading, sequence number and gloss.
+///
+/// Kanji and readings are also indexed in sorted order, for prefix, suffix and wildcard searches.
+#[derive(Debug, Default, Clone)]
+pub struct JMDict {
+    entities: Entities,
+    entries: Vec<JMDictEntry>,
+    kanji_index: HashMap<String, Vec<usize>>,
+    reading_index: HashMap<String, Vec<usize>>,
+    sequence_index: HashMap<u32, usize>,
+    gloss_index: GlossIndex,
+    form_index: FormIndex,
+}
+
+impl JMDict {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn from_entries<I: IntoIterator<Item = JMDictEntry>>(entries: I) -> Self {
+        let mut jmdict = Self::new();
+        for entry in entries {
+            jmdict.add_entry(entry);
+        }
+        jmdict
+    }
+    /// Parse a gzip-compressed JMdict file into memory.
+    #[cfg(feature = "reader")]
+    pub fn from_jmdict_gz<R: std::io::Read>(reader: R) -> Result<Self, crate::Error> {
+        use flate2::read::GzDecoder;
+        use std::io::BufReader;
+        Self::from_jmdict(BufReader::new(GzDecoder::new(reader)))
+    }
+    /// Parse a JMdict file into memory, plain or gzip, xz or zstd compressed.
+    #[cfg(feature = "reader")]
+    pub fn from_jmdict_any<R: std::io::Read>(reader: R) -> Result<Self, crate::Error> {
+        Self::from_jmdict(super::compression::decompress(reader)?)
+    }
+    /// Parse an uncompressed JMdict file into memory.
+    #[cfg(feature = "reader")]
+    pub fn from_jmdict<R: std::io::BufRead>(buf_reader: R) -> Result<Self, crate::Error> {
+        let mut jmdict = Self::new();
+        let mut jmdict_reader = super::reader::JMDictReader::new(buf_reader);
+        for entry_result in &mut jmdict_reader {
+            jmdict.add_entry(entry_result?);
+        }
+        jmdict.set_entities(jmdict_reader.into_entities());
+        Ok(jmdict)
+    }
+    /// Write the dictionary as a JMdict XML file.
+    pub fn write_jmdict<W: std::io::Write>(&self, writer: W) -> Result<W, crate::Error> {
+        super::writer::write_jmdict(writer, &self.entities, &self.entries)
+    }
+    /// Entity definitions of the dictionary, describing the tags of its entries.
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+    pub fn set_entities(&mut self, entities: Entities) {
+        self.entities = entities;
+    }
+    pub fn add_entry(&mut self, entry: JMDictEntry) {
+        let index = self.entries.len();
+        for kanji in entry.kanji() {
+            self.kanji_index
+                .entry(kana::normalize(kanji.string()))
+                .or_default()
+                .push(index);
+        }
+        for reading in entry.readings() {
+            self.reading_index
+                .entry(kana::normalize(reading.string()))
+                .or_default()
+                .push(index);
+        }
+        self.sequence_index.insert(entry.sequence(), index);
+        self.gloss_index.add_entry(index, &entry);
+        self.form_index.add_entry(index, &entry);
+        self.entries.push(entry);
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn entries(&self) -> &[JMDictEntry] {
+        &self.entries
+    }
+    /// Entries having the given kanji spelling, in dictionary order.
+    ///
+    /// Kana in the spelling matches regardless of script and width.
+    pub fn by_kanji<'s>(&'s self, kanji: &str) -> impl Iterator<Item = &'s JMDictEntry> {
+        self.indexed_entries(self.kanji_index.get(&kana::normalize(kanji)))
+    }
+    /// Entries having the given kana reading, in dictionary order.
+    ///
+    /// The reading matches regardless of kana script and width, and may be given in romaji.
+    pub fn by_reading<'s>(&'s self, reading: &str) -> impl Iterator<Item = &'s JMDictEntry> {
+        self.indexed_entries(self.reading_index.get(&kana::normalize_word(reading)))
+    }
+    pub fn by_sequence(&self, sequence: u32) -> Option<&JMDictEntry> {
+        self.sequence_index
+            .get(&sequence)
+            .map(|&index| &self.entries[index])
+    }
+    /// All the entries matching an entry identifier, most common first.
+    ///
+    /// A reading in romaji designates the entries spelled exactly this way if there are some,
+    /// such as abbreviations, and the entries with this reading otherwise.
+    pub fn lookup(&self, entry_id: &JMDictEntryId) -> Vec<&JMDictEntry> {
+        let mut entries: Vec<&JMDictEntry> = match entry_id {
+            JMDictEntryId::Kanji(kanji_string) => self.by_kanji(kanji_string).collect(),
+            JMDictEntryId::Sequence(sequence) => self.by_sequence(*sequence).into_iter().collect(),
+            JMDictEntryId::Reading(reading_string) => {
+                let spelled_entries: Vec<&JMDictEntry> =
+                    if kana::romaji_to_hiragana(reading_string).is_some() {
+                        self.by_kanji(reading_string).collect()
+                    } else {
+                        Vec::new()
+                    };
+                if spelled_entries.is_empty() {
+                    self.by_reading(reading_string).collect()
+                } else {
+                    spelled_entries
+                }
+            }
+        };
+        sort_by_priority(&mut entries);
+        entries
+    }
+    /// Dictionary forms of a conjugated word, such as 食べる for 食べました.
+    ///
+    /// Returns the entries of the first deinflection candidate found in the dictionary with a
+    /// matching part of speech, most common first, along with the conjugations recognized, from
+    /// the innermost to the outermost. Returns no entries if the word is not a known conjugated
+    /// form.
+    pub fn lookup_inflected(&self, word: &str) -> (Vec<&JMDictEntry>, Vec<Inflection>) {
+        let word = kana::romaji_to_hiragana(word).unwrap_or_else(|| String::from(word));
+        for deinflection in deinflect::deinflect(&word) {
+            if deinflection.is_original() {
+                continue;
+            }
+            let mut entries: Vec<&JMDictEntry> = Vec::new();
+            {
+                let forms = deinflection
+                    .suru_noun()
+                    .into_iter()
+                    .chain(Some(deinflection.word()));
+                for form in forms {
+                    let form_entries = self.by_kanji(form).chain(self.by_reading(form));
+                    for entry in form_entries.filter(|entry| deinflection.matches_entry(entry)) {
+                        if !entries.iter().any(|e| e.sequence() == entry.sequence()) {
+                            entries.push(entry);
+                        }
+                    }
+                }
+            }
+            if !entries.is_empty() {
+                sort_by_priority(&mut entries);
+                return (entries, deinflection.inflections().to_vec());
+            }
+        }
+        (Vec::new(), Vec::new())
+    }
+    /// Entries having an English gloss containing every word of the query, best matches first.
+    pub fn search_glosses(&self, query: &str) -> Vec<&JMDictEntry> {
+        self.gloss_index
+            .search(&self.entries, query)
+            .into_iter()
+            .map(|gloss_match| &self.entries[gloss_match.entry_index()])
+            .collect()
+    }
+    /// Entries having a kanji or reading matching a pattern such as `食*`, most common first.
+    pub fn search_forms(&self, pattern: &WordPattern) -> Vec<&JMDictEntry> {
+        let mut entries: Vec<&JMDictEntry> = self
+            .form_index
+            .search(pattern)
+            .into_iter()
+            .map(|index| &self.entries[index])
+            .collect();
+        sort_by_priority(&mut entries);
+        entries
+    }
+    fn indexed_entries<'s>(
+        &'s self,
+        indices: Option<&'s Vec<usize>>,
+    ) -> impl Iterator<Item = &'s JMDictEntry> {
+        indices
+            .into_iter()
+            .flatten()
+            .map(move |&index| &self.entries[index])
+    }
+}
+
+/// Sort entries by decreasing priority score, keeping dictionary order between equal scores.
+fn sort_by_priority(entries: &mut [&JMDictEntry]) {
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.priority_score()));
+}
@@ -0,0 +1,467 @@
+//! Differences between two releases of the JMdict dictionary, matching entries by their `ent_seq`
+//! sequence number.
+
+use super::dictionary::JMDict;
+use super::entry::{
+    CrossReference, Gloss, JMDictEntry, Kanji, LanguageSource, Reading, Sense, ENGLISH,
+};
+use super::entry_id::JMDictEntryId;
+use super::tags::PartOfSpeech;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Element of an entry holding a changed field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum EntryElement {
+    Kanji,
+    Reading,
+    Sense,
+}
+
+impl EntryElement {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntryElement::Kanji => "kanji",
+            EntryElement::Reading => "reading",
+            EntryElement::Sense => "sense",
+        }
+    }
+}
+
+/// Change of a single field of a kanji, reading or sense of an entry.
+///
+/// Fields are written as text, lists being joined with `, ` (`; ` for glosses). A field is `None`
+/// when empty, or when its element does not exist in one of the releases.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FieldChange {
+    element: EntryElement,
+    number: usize,
+    field: String,
+    old: Option<String>,
+    new: Option<String>,
+}
+
+impl FieldChange {
+    pub fn element(&self) -> EntryElement {
+        self.element
+    }
+    /// Position of the element in its list in the new release, or in the old release for a
+    /// removed element, starting at 1 as in sense numbers.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+    /// Name of the field, such as `priority` or `glosses`.
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+    pub fn old_value(&self) -> Option<&str> {
+        self.old.as_ref().map(|s| s as &str)
+    }
+    pub fn new_value(&self) -> Option<&str> {
+        self.new.as_ref().map(|s| s as &str)
+    }
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}: {} -> {}",
+            self.element.name(),
+            self.number,
+            self.field,
+            self.old.as_ref().map(|s| s as &str).unwrap_or("(none)"),
+            self.new.as_ref().map(|s| s as &str).unwrap_or("(none)")
+        )
+    }
+}
+
+/// Change of an entry between two releases.
+#[derive(Debug, Clone, Serialize)]
+pub enum EntryChange<'a> {
+    Added(&'a JMDictEntry),
+    Removed(&'a JMDictEntry),
+    Modified {
+        old: &'a JMDictEntry,
+        new: &'a JMDictEntry,
+        changes: Vec<FieldChange>,
+    },
+}
+
+impl<'a> EntryChange<'a> {
+    pub fn sequence(&self) -> u32 {
+        match self {
+            EntryChange::Added(entry) | EntryChange::Removed(entry) => entry.sequence(),
+            EntryChange::Modified { new, .. } => new.sequence(),
+        }
+    }
+    /// Entry in the old release, `None` if it was added.
+    pub fn old_entry(&self) -> Option<&'a JMDictEntry> {
+        match self {
+            EntryChange::Added(_) => None,
+            EntryChange::Removed(entry) => Some(entry),
+            EntryChange::Modified { old, .. } => Some(old),
+        }
+    }
+    /// Entry in the new release, `None` if it was removed.
+    pub fn new_entry(&self) -> Option<&'a JMDictEntry> {
+        match self {
+            EntryChange::Added(entry) => Some(entry),
+            EntryChange::Removed(_) => None,
+            EntryChange::Modified { new, .. } => Some(new),
+        }
+    }
+    /// Changed fields of a modified entry, empty for added and removed entries.
+    pub fn field_changes(&self) -> &[FieldChange] {
+        match self {
+            EntryChange::Modified { changes, .. } => changes,
+            _ => &[],
+        }
+    }
+    /// Whether an entry identifier designates the entry in either release.
+    pub fn matches(&self, entry_id: &JMDictEntryId) -> bool {
+        self.old_entry()
+            .into_iter()
+            .chain(self.new_entry())
+            .any(|entry| entry_id.match_entry(entry))
+    }
+}
+
+/// Changed entries between two releases, in sequence number order.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DictionaryDiff<'a> {
+    changes: Vec<EntryChange<'a>>,
+}
+
+impl<'a> DictionaryDiff<'a> {
+    pub fn changes(&self) -> &[EntryChange<'a>] {
+        &self.changes
+    }
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+    pub fn added(&self) -> impl Iterator<Item = &EntryChange<'a>> {
+        self.changes
+            .iter()
+            .filter(|change| matches!(change, EntryChange::Added(_)))
+    }
+    pub fn removed(&self) -> impl Iterator<Item = &EntryChange<'a>> {
+        self.changes
+            .iter()
+            .filter(|change| matches!(change, EntryChange::Removed(_)))
+    }
+    pub fn modified(&self) -> impl Iterator<Item = &EntryChange<'a>> {
+        self.changes
+            .iter()
+            .filter(|change| matches!(change, EntryChange::Modified { .. }))
+    }
+    /// Changes affecting the words of a word list, along with the identifier of each word.
+    ///
+    /// A word identified by its kanji or reading may be affected by several changes, for example
+    /// when one of its homonyms is removed.
+    pub fn affected_words<'s, 'i, 'x>(
+        &'s self,
+        entry_ids: &'i [JMDictEntryId<'x>],
+    ) -> Vec<(&'i JMDictEntryId<'x>, &'s EntryChange<'a>)> {
+        entry_ids
+            .iter()
+            .flat_map(|entry_id| {
+                self.changes
+                    .iter()
+                    .filter(move |change| change.matches(entry_id))
+                    .map(move |change| (entry_id, change))
+            })
+            .collect()
+    }
+}
+
+/// Compare two releases of the dictionary, matching their entries by sequence number.
+pub fn diff<'a>(old: &'a JMDict, new: &'a JMDict) -> DictionaryDiff<'a> {
+    let mut changes: Vec<EntryChange<'a>> = Vec::new();
+    for old_entry in old.entries() {
+        match new.by_sequence(old_entry.sequence()) {
+            Some(new_entry) => {
+                let field_changes = diff_entries(old_entry, new_entry);
+                if !field_changes.is_empty() {
+                    changes.push(EntryChange::Modified {
+                        old: old_entry,
+                        new: new_entry,
+                        changes: field_changes,
+                    });
+                }
+            }
+            None => changes.push(EntryChange::Removed(old_entry)),
+        }
+    }
+    for new_entry in new.entries() {
+        if old.by_sequence(new_entry.sequence()).is_none() {
+            changes.push(EntryChange::Added(new_entry));
+        }
+    }
+    changes.sort_by_key(EntryChange::sequence);
+    DictionaryDiff { changes }
+}
+
+/// Changed fields between two releases of an entry.
+///
+/// Kanji and readings are matched by their text, and senses by their set of glosses, so that an
+/// inserted or removed element does not show up as a change of every element after it. Elements
+/// left unmatched in both releases are then compared in order.
+pub fn diff_entries(old: &JMDictEntry, new: &JMDictEntry) -> Vec<FieldChange> {
+    let mut changes: Vec<FieldChange> = Vec::new();
+    diff_elements(
+        &mut changes,
+        EntryElement::Kanji,
+        old.kanji(),
+        new.kanji(),
+        |kanji| String::from(kanji.string()),
+        kanji_fields,
+    );
+    diff_elements(
+        &mut changes,
+        EntryElement::Reading,
+        old.readings(),
+        new.readings(),
+        |reading| String::from(reading.string()),
+        reading_fields,
+    );
+    diff_elements(
+        &mut changes,
+        EntryElement::Sense,
+        &senses_of(old),
+        &senses_of(new),
+        sense_key,
+        sense_fields,
+    );
+    changes
+}
+
+/// Sense of an entry, with the parts of speech it inherits from the previous senses.
+type EntrySense<'a> = (&'a Sense, &'a [PartOfSpeech]);
+
+fn senses_of(entry: &JMDictEntry) -> Vec<EntrySense<'_>> {
+    entry
+        .senses()
+        .iter()
+        .enumerate()
+        .map(|(index, sense)| (sense, entry.sense_parts_of_speech(index)))
+        .collect()
+}
+
+/// Pairs of matching elements of two releases, by position in the old and the new release.
+///
+/// Elements with the same key are paired first, then the remaining ones in order. Elements only
+/// found in one release are paired with `None`.
+fn match_elements<T>(
+    old_list: &[T],
+    new_list: &[T],
+    element_key: fn(&T) -> String,
+) -> Vec<(Option<usize>, Option<usize>)> {
+    let old_keys: Vec<String> = old_list.iter().map(element_key).collect();
+    let mut old_matched = vec![false; old_list.len()];
+    let mut new_matches: Vec<Option<usize>> = new_list
+        .iter()
+        .map(|new_element| {
+            let new_key = element_key(new_element);
+            let old_index = (0..old_list.len())
+                .find(|&index| !old_matched[index] && old_keys[index] == new_key)?;
+            old_matched[old_index] = true;
+            Some(old_index)
+        })
+        .collect();
+    let mut old_unmatched = (0..old_list.len()).filter(|&index| !old_matched[index]);
+    for new_match in new_matches.iter_mut().filter(|m| m.is_none()) {
+        *new_match = old_unmatched.next();
+    }
+    let mut pairs: Vec<(Option<usize>, Option<usize>)> = new_matches
+        .into_iter()
+        .enumerate()
+        .map(|(new_index, old_index)| (old_index, Some(new_index)))
+        .collect();
+    pairs.extend(old_unmatched.map(|old_index| (Some(old_index), None)));
+    pairs
+}
+
+fn diff_elements<T>(
+    changes: &mut Vec<FieldChange>,
+    element: EntryElement,
+    old_list: &[T],
+    new_list: &[T],
+    element_key: fn(&T) -> String,
+    element_fields: fn(&T) -> Vec<(&'static str, String)>,
+) {
+    for (old_index, new_index) in match_elements(old_list, new_list, element_key) {
+        let old_fields = old_index
+            .map(|index| element_fields(&old_list[index]))
+            .unwrap_or_default();
+        let new_fields = new_index
+            .map(|index| element_fields(&new_list[index]))
+            .unwrap_or_default();
+        // number in the new release, or in the old one for removed elements
+        let number = new_index.or(old_index).unwrap_or_default() + 1;
+        let field_names = old_fields.iter().chain(&new_fields).map(|&(name, _)| name);
+        let mut seen_names: Vec<&'static str> = Vec::new();
+        for name in field_names {
+            if seen_names.contains(&name) {
+                continue;
+            }
+            seen_names.push(name);
+            let old_value = field_value(&old_fields, name);
+            let new_value = field_value(&new_fields, name);
+            if old_value != new_value {
+                changes.push(FieldChange {
+                    element,
+                    number,
+                    field: String::from(name),
+                    old: old_value,
+                    new: new_value,
+                });
+            }
+        }
+    }
+}
+
+fn field_value(fields: &[(&'static str, String)], name: &str) -> Option<String> {
+    fields
+        .iter()
+        .find(|&&(field_name, _)| field_name == name)
+        .map(|(_, value)| value.clone())
+        .filter(|value| !value.is_empty())
+}
+
+fn join<I: IntoIterator<Item = S>, S: AsRef<str>>(values: I, separator: &str) -> String {
+    values
+        .into_iter()
+        .map(|value| String::from(value.as_ref()))
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+fn kanji_fields(kanji: &Kanji) -> Vec<(&'static str, String)> {
+    vec![
+        ("text", String::from(kanji.string())),
+        ("info", join(kanji.info().iter().map(|i| i.code()), ", ")),
+        (
+            "priority",
+            join(kanji.priority().iter().map(|p| p.code()), ", "),
+        ),
+    ]
+}
+
+fn reading_fields(reading: &Reading) -> Vec<(&'static str, String)> {
+    vec![
+        ("text", String::from(reading.string())),
+        (
+            "no_kanji",
+            if reading.no_kanji() {
+                String::from("yes")
+            } else {
+                String::new()
+            },
+        ),
+        ("restrictions", join(reading.restrictions(), ", ")),
+        ("info", join(reading.info().iter().map(|i| i.code()), ", ")),
+        (
+            "priority",
+            join(reading.priority().iter().map(|p| p.code()), ", "),
+        ),
+    ]
+}
+
+/// Glosses of a sense, regardless of their order.
+fn sense_key(&(sense, _): &EntrySense<'_>) -> String {
+    let mut glosses: Vec<String> = sense.glosses().iter().map(gloss_text).collect();
+    glosses.sort();
+    glosses.join("; ")
+}
+
+fn sense_fields(&(sense, parts_of_speech): &EntrySense<'_>) -> Vec<(&'static str, String)> {
+    vec![
+        (
+            "glosses",
+            join(sense.glosses().iter().map(gloss_text), "; "),
+        ),
+        (
+            "parts_of_speech",
+            join(parts_of_speech.iter().map(|p| p.code()), ", "),
+        ),
+        ("kanji_restrictions", join(sense.kanji_restrictions(), ", ")),
+        (
+            "reading_restrictions",
+            join(sense.reading_restrictions(), ", "),
+        ),
+        (
+            "cross_references",
+            join(sense.cross_references().iter().map(reference_text), ", "),
+        ),
+        (
+            "antonyms",
+            join(sense.antonyms().iter().map(reference_text), ", "),
+        ),
+        (
+            "fields",
+            join(sense.fields().iter().map(|f| f.code()), ", "),
+        ),
+        ("misc", join(sense.misc().iter().map(|m| m.code()), ", ")),
+        ("info", join(sense.info(), ", ")),
+        (
+            "language_sources",
+            join(
+                sense.language_sources().iter().map(language_source_text),
+                ", ",
+            ),
+        ),
+        (
+            "dialects",
+            join(sense.dialects().iter().map(|d| d.code()), ", "),
+        ),
+    ]
+}
+
+/// Gloss with its language when not English, its type and its gender, such as `[fre] chat (fem)`.
+fn gloss_text(gloss: &Gloss) -> String {
+    let mut text = match gloss.lang() {
+        Some(lang) => format!("[{}] {}", lang, gloss.text()),
+        None => String::from(gloss.text()),
+    };
+    let attributes: Vec<&str> = gloss
+        .gloss_type()
+        .map(|gloss_type| gloss_type.code())
+        .into_iter()
+        .chain(gloss.gender())
+        .collect();
+    if !attributes.is_empty() {
+        text.push_str(&format!(" ({})", attributes.join(", ")));
+    }
+    text
+}
+
+/// Reference written as in JMdict, such as `来る・くる・1`.
+fn reference_text(reference: &CrossReference) -> String {
+    let sense = reference.sense().map(|sense| sense.to_string());
+    join(
+        Some(reference.text())
+            .into_iter()
+            .chain(reference.reading())
+            .chain(sense.as_ref().map(|s| s as &str)),
+        "・",
+    )
+}
+
+fn language_source_text(language_source: &LanguageSource) -> String {
+    let mut text = format!(
+        "[{}] {}",
+        language_source.lang().unwrap_or(ENGLISH),
+        language_source.text()
+    );
+    if language_source.partial() {
+        text.push_str(" (part)");
+    }
+    if language_source.wasei() {
+        text.push_str(" (wasei)");
+    }
+    text
+}
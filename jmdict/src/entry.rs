@@ -1,33 +1,39 @@
+use super::tags::{Dialect, Field, GlossType, KanjiInfo, Misc, PartOfSpeech, ReadingInfo};
 use super::JMDictEntryId;
 use serde::{Deserialize, Serialize};
 
+/// ISO 639-2 code of English, the language of glosses without an `xml:lang` attribute.
+pub const ENGLISH: &str = "eng";
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct JMDictEntry {
-    entry_id: JMDictEntryId<'static>,
+    sequence: u32,
     kanji_list: Vec<Kanji>,
     reading_list: Vec<Reading>,
     sense_list: Vec<Sense>,
 }
 
 impl JMDictEntry {
-    pub fn new<'a>(
-        entry_id: JMDictEntryId<'a>,
+    pub fn new(
+        sequence: u32,
         kanji_list: Vec<Kanji>,
         reading_list: Vec<Reading>,
         sense_list: Vec<Sense>,
     ) -> Self {
-        let my_entry_id: JMDictEntryId<'static> = match entry_id {
-            JMDictEntryId::Kanji(kanji_cow) => JMDictEntryId::Kanji(kanji_cow.into_owned().into()),
-        };
         Self {
-            entry_id: my_entry_id,
+            sequence,
             kanji_list,
             reading_list,
             sense_list,
         }
     }
-    pub fn entry_id(&self) -> &JMDictEntryId {
-        &self.entry_id
+    /// Stable identifier of the entry, based on its sequence number.
+    pub fn entry_id(&self) -> JMDictEntryId<'static> {
+        JMDictEntryId::Sequence(self.sequence)
+    }
+    /// JMdict `ent_seq` sequence number.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
     }
     pub fn kanji(&self) -> &[Kanji] {
         &self.kanji_list
@@ -38,43 +44,375 @@ impl JMDictEntry {
     pub fn senses(&self) -> &[Sense] {
         &self.sense_list
     }
+    /// Main spelling of the entry: its first common kanji, if any.
+    pub fn main_kanji(&self) -> Option<&Kanji> {
+        self.kanji_list
+            .iter()
+            .find(|k| !k.is_rare())
+            .or_else(|| self.kanji_list.first())
+    }
+    /// Main reading of the entry: its first common reading applying to the main kanji.
+    pub fn main_reading(&self) -> Option<&Reading> {
+        self.main_kanji()
+            .and_then(|k| self.readings_for_kanji(k).find(|r| !r.is_rare()))
+            .or_else(|| self.reading_list.iter().find(|r| !r.is_rare()))
+            .or_else(|| self.reading_list.first())
+    }
+    /// Parts of speech of a sense, inherited from the previous senses when not specified.
+    pub fn sense_parts_of_speech(&self, sense_index: usize) -> &[PartOfSpeech] {
+        let sense_end = (sense_index + 1).min(self.sense_list.len());
+        self.sense_list[..sense_end]
+            .iter()
+            .rev()
+            .map(Sense::parts_of_speech)
+            .find(|parts_of_speech| !parts_of_speech.is_empty())
+            .unwrap_or(&[])
+    }
+    /// How common the entry is, from the priority codes of its most common kanji or reading.
+    ///
+    /// Higher scores are more common; entries without any priority code score 0.
+    pub fn priority_score(&self) -> u32 {
+        let kanji_scores = self.kanji_list.iter().map(|k| priority_score(k.priority()));
+        let reading_scores = self
+            .reading_list
+            .iter()
+            .map(|r| priority_score(r.priority()));
+        kanji_scores.chain(reading_scores).max().unwrap_or(0)
+    }
+    /// Whether the entry is a common word, as marked by JMdict.
+    pub fn is_common(&self) -> bool {
+        let kanji_priorities = self.kanji_list.iter().flat_map(|k| k.priority());
+        let reading_priorities = self.reading_list.iter().flat_map(|r| r.priority());
+        kanji_priorities
+            .chain(reading_priorities)
+            .any(Priority::is_common)
+    }
+    /// Keep only the glosses in the given ISO 639-2 languages, such as `fre` or `ger`.
+    ///
+    /// English glosses are kept instead if the entry has none in these languages, and senses left
+    /// without glosses are removed. An empty language list keeps every gloss.
+    ///
+    /// The English fallback applies to the whole entry rather than to each sense: JMdict puts the
+    /// glosses of other languages in senses of their own, after the English ones, so a per-sense
+    /// fallback would keep every English sense along with the selected ones.
+    ///
+    /// Parts of speech inherited from a removed sense are copied to the sense following it, so
+    /// that `sense_parts_of_speech` is unchanged for the remaining senses.
+    pub fn select_languages<S: AsRef<str>>(&mut self, languages: &[S]) {
+        if languages.is_empty() {
+            return;
+        }
+        let is_selected = |gloss: &Gloss| languages.iter().any(|l| l.as_ref() == gloss.language());
+        let has_selected = self
+            .sense_list
+            .iter()
+            .flat_map(Sense::glosses)
+            .any(&is_selected);
+        let mut removed_parts_of_speech: Option<Vec<PartOfSpeech>> = None;
+        for sense in &mut self.sense_list {
+            if has_selected {
+                sense.gloss_list.retain(|gloss| is_selected(gloss));
+            } else {
+                sense.gloss_list.retain(|gloss| gloss.language() == ENGLISH);
+            }
+            if !sense.parts_of_speech.is_empty() {
+                removed_parts_of_speech = if sense.gloss_list.is_empty() {
+                    Some(sense.parts_of_speech.clone())
+                } else {
+                    None
+                };
+            } else if !sense.gloss_list.is_empty() {
+                if let Some(parts_of_speech) = removed_parts_of_speech.take() {
+                    sense.parts_of_speech = parts_of_speech;
+                }
+            }
+        }
+        self.sense_list.retain(|sense| !sense.gloss_list.is_empty());
+    }
+    /// Readings applying to a kanji spelling of this entry.
+    pub fn readings_for_kanji<'s>(&'s self, kanji: &'s Kanji) -> impl Iterator<Item = &'s Reading> {
+        self.reading_list
+            .iter()
+            .filter(move |reading| reading.applies_to_kanji(kanji.string()))
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct Kanji(String);
+pub struct Kanji {
+    text: String,
+    info: Vec<KanjiInfo>,
+    priority: Vec<Priority>,
+}
 
 impl Kanji {
     pub fn new(kanji_string: String) -> Self {
-        Self(kanji_string)
+        Self {
+            text: kanji_string,
+            info: Vec::new(),
+            priority: Vec::new(),
+        }
     }
     pub fn string(&self) -> &str {
-        &self.0
+        &self.text
+    }
+    /// Information from `<ke_inf>` (for example ateji or irregular kanji usage).
+    pub fn info(&self) -> &[KanjiInfo] {
+        &self.info
+    }
+    pub fn add_info(&mut self, info: KanjiInfo) {
+        self.info.push(info);
+    }
+    /// Priority codes from `<ke_pri>`.
+    pub fn priority(&self) -> &[Priority] {
+        &self.priority
+    }
+    pub fn add_priority(&mut self, priority: Priority) {
+        self.priority.push(priority);
+    }
+    /// Whether this spelling is irregular, out-dated, rarely used or search-only.
+    pub fn is_rare(&self) -> bool {
+        self.info.iter().any(|info| info.is_rare())
     }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct Reading(String);
+pub struct Reading {
+    text: String,
+    no_kanji: bool,
+    restrictions: Vec<String>,
+    info: Vec<ReadingInfo>,
+    priority: Vec<Priority>,
+}
 
 impl Reading {
     pub fn new(kana_string: String) -> Self {
-        Self(kana_string)
+        Self {
+            text: kana_string,
+            no_kanji: false,
+            restrictions: Vec::new(),
+            info: Vec::new(),
+            priority: Vec::new(),
+        }
     }
     pub fn string(&self) -> &str {
-        &self.0
+        &self.text
+    }
+    /// Set from `<re_nokanji>`: this reading is not a true reading of any of the kanji.
+    pub fn no_kanji(&self) -> bool {
+        self.no_kanji
+    }
+    pub fn set_no_kanji(&mut self, no_kanji: bool) {
+        self.no_kanji = no_kanji;
+    }
+    /// Kanji spellings this reading is restricted to, from `<re_restr>`.
+    ///
+    /// An empty list means the reading applies to every kanji of the entry.
+    pub fn restrictions(&self) -> &[String] {
+        &self.restrictions
+    }
+    pub fn add_restriction(&mut self, kanji_string: String) {
+        self.restrictions.push(kanji_string);
+    }
+    /// Information from `<re_inf>` (for example irregular or out-dated kana usage).
+    pub fn info(&self) -> &[ReadingInfo] {
+        &self.info
+    }
+    pub fn add_info(&mut self, info: ReadingInfo) {
+        self.info.push(info);
+    }
+    /// Priority codes from `<re_pri>`.
+    pub fn priority(&self) -> &[Priority] {
+        &self.priority
+    }
+    pub fn add_priority(&mut self, priority: Priority) {
+        self.priority.push(priority);
     }
+    /// Whether this reading is irregular, out-dated, rarely used or search-only.
+    pub fn is_rare(&self) -> bool {
+        self.info.iter().any(|info| info.is_rare())
+    }
+    /// Whether this reading applies to the given kanji spelling.
+    pub fn applies_to_kanji(&self, kanji_string: &str) -> bool {
+        !self.no_kanji
+            && (self.restrictions.is_empty() || self.restrictions.iter().any(|k| k == kanji_string))
+    }
+}
+
+/// Priority code from `<ke_pri>` and `<re_pri>` elements.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// `news1` or `news2`: Mainichi Shimbun word frequency file
+    News(u8),
+    /// `ichi1` or `ichi2`: "Ichimango goi bunruishuu"
+    Ichi(u8),
+    /// `spec1` or `spec2`: common words not in the other lists
+    Spec(u8),
+    /// `gai1` or `gai2`: common loanwords
+    Gai(u8),
+    /// `nf01` to `nf48`: frequency rank in sets of 500 words
+    Frequency(u8),
+}
+
+impl Priority {
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim();
+        let split_index = code.find(|c: char| c.is_ascii_digit())?;
+        let (list, rank_string) = code.split_at(split_index);
+        let rank: u8 = rank_string.parse().ok()?;
+        match list {
+            "news" => Some(Priority::News(rank)),
+            "ichi" => Some(Priority::Ichi(rank)),
+            "spec" => Some(Priority::Spec(rank)),
+            "gai" => Some(Priority::Gai(rank)),
+            "nf" => Some(Priority::Frequency(rank)),
+            _ => None,
+        }
+    }
+    /// Weight of this code in the priority score of an entry.
+    ///
+    /// Codes of the first half of a list weigh more than those of the second half, and `nfXX`
+    /// codes weigh more the more frequent the word is.
+    pub fn score(&self) -> u32 {
+        match *self {
+            Priority::News(1) | Priority::Ichi(1) | Priority::Spec(1) | Priority::Gai(1) => 20,
+            Priority::News(_) | Priority::Ichi(_) | Priority::Spec(_) | Priority::Gai(_) => 10,
+            Priority::Frequency(rank) => 49u32.saturating_sub(u32::from(rank)),
+        }
+    }
+    /// Whether this code marks a common word: `news1`, `ichi1`, `spec1`, `spec2` or `gai1`.
+    pub fn is_common(&self) -> bool {
+        match *self {
+            Priority::News(rank) | Priority::Ichi(rank) | Priority::Gai(rank) => rank == 1,
+            Priority::Spec(_) => true,
+            Priority::Frequency(_) => false,
+        }
+    }
+    pub fn code(&self) -> String {
+        match self {
+            Priority::News(rank) => format!("news{}", rank),
+            Priority::Ichi(rank) => format!("ichi{}", rank),
+            Priority::Spec(rank) => format!("spec{}", rank),
+            Priority::Gai(rank) => format!("gai{}", rank),
+            Priority::Frequency(rank) => format!("nf{:02}", rank),
+        }
+    }
+}
+
+/// Priority score of a kanji or reading, summing the weights of its priority codes.
+fn priority_score(priorities: &[Priority]) -> u32 {
+    priorities.iter().map(Priority::score).sum()
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Sense {
+    kanji_restrictions: Vec<String>,
+    reading_restrictions: Vec<String>,
+    parts_of_speech: Vec<PartOfSpeech>,
+    cross_references: Vec<CrossReference>,
+    antonyms: Vec<CrossReference>,
+    fields: Vec<Field>,
+    misc: Vec<Misc>,
+    info: Vec<String>,
+    language_sources: Vec<LanguageSource>,
+    dialects: Vec<Dialect>,
     gloss_list: Vec<Gloss>,
 }
 
+impl Default for Sense {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Sense {
     pub fn new() -> Self {
         Sense {
+            kanji_restrictions: Vec::new(),
+            reading_restrictions: Vec::new(),
+            parts_of_speech: Vec::new(),
+            cross_references: Vec::new(),
+            antonyms: Vec::new(),
+            fields: Vec::new(),
+            misc: Vec::new(),
+            info: Vec::new(),
+            language_sources: Vec::new(),
+            dialects: Vec::new(),
             gloss_list: Vec::new(),
         }
     }
+    /// Kanji spellings this sense is restricted to, from `<stagk>`.
+    pub fn kanji_restrictions(&self) -> &[String] {
+        &self.kanji_restrictions
+    }
+    pub fn add_kanji_restriction(&mut self, kanji_string: String) {
+        self.kanji_restrictions.push(kanji_string);
+    }
+    /// Readings this sense is restricted to, from `<stagr>`.
+    pub fn reading_restrictions(&self) -> &[String] {
+        &self.reading_restrictions
+    }
+    pub fn add_reading_restriction(&mut self, kana_string: String) {
+        self.reading_restrictions.push(kana_string);
+    }
+    /// Parts of speech from `<pos>`.
+    ///
+    /// Empty when the sense uses the parts of speech of the previous sense, see
+    /// `JMDictEntry::sense_parts_of_speech`.
+    pub fn parts_of_speech(&self) -> &[PartOfSpeech] {
+        &self.parts_of_speech
+    }
+    pub fn add_part_of_speech(&mut self, part_of_speech: PartOfSpeech) {
+        self.parts_of_speech.push(part_of_speech);
+    }
+    /// Related entries from `<xref>`.
+    pub fn cross_references(&self) -> &[CrossReference] {
+        &self.cross_references
+    }
+    pub fn add_cross_reference(&mut self, cross_reference: CrossReference) {
+        self.cross_references.push(cross_reference);
+    }
+    /// Entries of opposite meaning from `<ant>`.
+    pub fn antonyms(&self) -> &[CrossReference] {
+        &self.antonyms
+    }
+    pub fn add_antonym(&mut self, antonym: CrossReference) {
+        self.antonyms.push(antonym);
+    }
+    /// Fields of application from `<field>`.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+    pub fn add_field(&mut self, field: Field) {
+        self.fields.push(field);
+    }
+    /// Miscellaneous information from `<misc>` (for example usually written in kana, or slang).
+    pub fn misc(&self) -> &[Misc] {
+        &self.misc
+    }
+    pub fn add_misc(&mut self, misc: Misc) {
+        self.misc.push(misc);
+    }
+    /// Free-form sense information from `<s_inf>`.
+    pub fn info(&self) -> &[String] {
+        &self.info
+    }
+    pub fn add_info(&mut self, info: String) {
+        self.info.push(info);
+    }
+    /// Loanword sources from `<lsource>`.
+    pub fn language_sources(&self) -> &[LanguageSource] {
+        &self.language_sources
+    }
+    pub fn add_language_source(&mut self, language_source: LanguageSource) {
+        self.language_sources.push(language_source);
+    }
+    /// Dialects from `<dial>`.
+    pub fn dialects(&self) -> &[Dialect] {
+        &self.dialects
+    }
+    pub fn add_dialect(&mut self, dialect: Dialect) {
+        self.dialects.push(dialect);
+    }
     pub fn add_gloss(&mut self, gloss: Gloss) {
         self.gloss_list.push(gloss);
     }
@@ -83,20 +421,212 @@ impl Sense {
     }
 }
 
+/// Reference to another entry from `<xref>` or `<ant>`, such as `来る・くる・1`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CrossReference {
+    text: String,
+    reading: Option<String>,
+    sense: Option<u32>,
+}
+
+impl CrossReference {
+    pub fn new(text: String, reading: Option<String>, sense: Option<u32>) -> Self {
+        Self {
+            text,
+            reading,
+            sense,
+        }
+    }
+    /// Parse a reference made of a kanji or reading, an optional reading and an optional sense
+    /// number, separated by `・`.
+    pub fn parse(reference_string: &str) -> Self {
+        const SEPARATOR: char = '・';
+        let mut parts: Vec<&str> = reference_string.split(SEPARATOR).collect();
+        let sense = match parts.last().and_then(|p| p.parse().ok()) {
+            Some(sense) if parts.len() > 1 => {
+                parts.pop();
+                Some(sense)
+            }
+            _ => None,
+        };
+        let text = String::from(parts.first().cloned().unwrap_or(""));
+        let reading = parts.get(1).map(|&r| String::from(r));
+        Self::new(text, reading, sense)
+    }
+    /// Kanji or reading of the referenced entry.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    pub fn reading(&self) -> Option<&str> {
+        self.reading.as_ref().map(|s| s as &str)
+    }
+    /// Number of the referenced sense, starting at 1.
+    pub fn sense(&self) -> Option<u32> {
+        self.sense
+    }
+}
+
+/// Source language of a loanword, from `<lsource>`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LanguageSource {
+    text: String,
+    lang: Option<String>,
+    partial: bool,
+    wasei: bool,
+}
+
+impl LanguageSource {
+    pub fn new(lang: Option<String>, partial: bool, wasei: bool) -> Self {
+        Self {
+            text: String::new(),
+            lang,
+            partial,
+            wasei,
+        }
+    }
+    /// Source word, may be empty when only the language is known.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+    }
+    /// ISO 639-2 language code, `None` meaning English.
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_ref().map(|s| s as &str)
+    }
+    /// Whether the source word only partially describes the Japanese word.
+    pub fn partial(&self) -> bool {
+        self.partial
+    }
+    /// Whether the word is wasei-eigo, a Japanese construction from foreign words.
+    pub fn wasei(&self) -> bool {
+        self.wasei
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct Gloss(String, Option<String>);
+pub struct Gloss {
+    text: String,
+    lang: Option<String>,
+    #[serde(default)]
+    gloss_type: Option<GlossType>,
+    #[serde(default)]
+    gender: Option<String>,
+}
 
 impl Gloss {
     pub fn new(gloss_string: String) -> Self {
-        Gloss(gloss_string, None)
+        Gloss {
+            text: gloss_string,
+            lang: None,
+            gloss_type: None,
+            gender: None,
+        }
     }
     pub fn new_with_lang(gloss_string: String, gloss_lang: String) -> Self {
-        Gloss(gloss_string, Some(gloss_lang))
+        Gloss {
+            lang: Some(gloss_lang),
+            ..Self::new(gloss_string)
+        }
     }
     pub fn text(&self) -> &str {
-        &self.0
+        &self.text
     }
     pub fn lang(&self) -> Option<&str> {
-        self.1.as_ref().map(|s| s as &str)
+        self.lang.as_ref().map(|s| s as &str)
+    }
+    /// Kind of gloss from `g_type`, `None` for a plain translation.
+    pub fn gloss_type(&self) -> Option<&GlossType> {
+        self.gloss_type.as_ref()
+    }
+    pub fn set_gloss_type(&mut self, gloss_type: GlossType) {
+        self.gloss_type = Some(gloss_type);
+    }
+    /// Grammatical gender of the gloss from `g_gend`, such as `masc`, `fem` or `neut`.
+    pub fn gender(&self) -> Option<&str> {
+        self.gender.as_ref().map(|s| s as &str)
+    }
+    pub fn set_gender(&mut self, gender: String) {
+        self.gender = Some(gender);
+    }
+    /// ISO 639-2 language code of the gloss, English when not specified.
+    pub fn language(&self) -> &str {
+        self.lang().unwrap_or(ENGLISH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(codes: &[&str]) -> Vec<Priority> {
+        codes
+            .iter()
+            .map(|code| Priority::from_code(code).unwrap())
+            .collect()
+    }
+
+    fn entry(kanji_codes: &[&str], reading_codes: &[&str]) -> JMDictEntry {
+        let mut kanji = Kanji::new(String::from("食べる"));
+        for priority in codes(kanji_codes) {
+            kanji.add_priority(priority);
+        }
+        let mut reading = Reading::new(String::from("たべる"));
+        for priority in codes(reading_codes) {
+            reading.add_priority(priority);
+        }
+        JMDictEntry::new(1358280, vec![kanji], vec![reading], Vec::new())
+    }
+
+    #[test]
+    fn score() {
+        let scores: Vec<u32> = codes(&[
+            "news1", "news2", "ichi1", "ichi2", "spec1", "spec2", "gai1", "gai2",
+        ])
+        .iter()
+        .map(Priority::score)
+        .collect();
+        assert_eq!(scores, vec![20, 10, 20, 10, 20, 10, 20, 10]);
+        let scores: Vec<u32> = codes(&["nf01", "nf24", "nf48"])
+            .iter()
+            .map(Priority::score)
+            .collect();
+        assert_eq!(scores, vec![48, 25, 1]);
+        // beyond the ranks used by JMdict
+        assert_eq!(Priority::Frequency(60).score(), 0);
+    }
+
+    #[test]
+    fn is_common() {
+        let common: Vec<bool> = codes(&[
+            "news1", "news2", "ichi1", "ichi2", "spec1", "spec2", "gai1", "gai2", "nf01",
+        ])
+        .iter()
+        .map(Priority::is_common)
+        .collect();
+        assert_eq!(
+            common,
+            vec![true, false, true, false, true, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn entry_priority() {
+        let uncommon = entry(&[], &[]);
+        assert_eq!(uncommon.priority_score(), 0);
+        assert!(!uncommon.is_common());
+        // second halves of the lists and frequency ranks only
+        let uncommon = entry(&["news2", "nf30"], &["ichi2"]);
+        assert_eq!(uncommon.priority_score(), 29);
+        assert!(!uncommon.is_common());
+        // the most common spelling counts, scores are not added across kanji and readings
+        let common = entry(&["news1", "nf05"], &["ichi1", "gai1", "spec1"]);
+        assert_eq!(common.priority_score(), 64);
+        assert!(common.is_common());
+        // a single common code, on a reading, is enough
+        let common = entry(&["news2"], &["spec2"]);
+        assert_eq!(common.priority_score(), 10);
+        assert!(common.is_common());
     }
 }
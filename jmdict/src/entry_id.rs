@@ -1,26 +1,127 @@
+use super::entry::JMDictEntry;
+use super::kana;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 
 #[derive(Clone, Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub enum JMDictEntryId<'a> {
     Kanji(Cow<'a, str>),
+    /// JMdict `ent_seq` sequence number, stable across dictionary releases
+    Sequence(u32),
+    /// Kana reading, for words usually or only written in kana
+    Reading(Cow<'a, str>),
 }
 
 impl<'a> JMDictEntryId<'a> {
     pub fn from_kanji<S: Into<Cow<'a, str>>>(s: S) -> Self {
         JMDictEntryId::Kanji(s.into())
     }
+    pub fn from_sequence(sequence: u32) -> Self {
+        JMDictEntryId::Sequence(sequence)
+    }
+    pub fn from_reading<S: Into<Cow<'a, str>>>(s: S) -> Self {
+        JMDictEntryId::Reading(s.into())
+    }
+    /// Identify a word typed by a user: by its reading if it is written only in kana or in
+    /// romaji, by its kanji otherwise.
+    ///
+    /// Romaji is kept as typed, since it may also be a spelling such as an abbreviation: it is
+    /// converted to kana when looking the word up, if no entry has this exact spelling.
+    pub fn from_word<S: Into<Cow<'a, str>>>(s: S) -> Self {
+        let word = s.into();
+        if kana::romaji_to_hiragana(&word).is_some()
+            || (!word.is_empty() && word.chars().all(kana::is_kana))
+        {
+            JMDictEntryId::Reading(word)
+        } else {
+            JMDictEntryId::Kanji(word)
+        }
+    }
+    /// Whether this identifier designates a kanji spelling, regardless of kana script and width.
     pub fn match_kanji(&self, kanji_string: &str) -> bool {
         match self {
-            JMDictEntryId::Kanji(ref my_kanji_string) => kanji_string == my_kanji_string,
+            JMDictEntryId::Kanji(ref my_kanji_string) => {
+                kana::normalize(kanji_string) == kana::normalize(my_kanji_string)
+            }
+            _ => false,
+        }
+    }
+    /// Whether this identifier designates a reading, regardless of kana script and width, the
+    /// identifier being possibly in romaji.
+    pub fn match_reading(&self, reading_string: &str) -> bool {
+        match self {
+            JMDictEntryId::Reading(ref my_reading_string) => {
+                kana::normalize(reading_string) == kana::normalize_word(my_reading_string)
+            }
+            _ => false,
+        }
+    }
+    pub fn match_sequence(&self, sequence: u32) -> bool {
+        match self {
+            JMDictEntryId::Sequence(my_sequence) => *my_sequence == sequence,
             _ => false,
         }
     }
+    /// Whether this identifier designates an entry: by sequence number, or by any of its kanji
+    /// or readings.
+    pub fn match_entry(&self, entry: &JMDictEntry) -> bool {
+        match self {
+            JMDictEntryId::Kanji(_) => entry.kanji().iter().any(|k| self.match_kanji(k.string())),
+            JMDictEntryId::Sequence(sequence) => entry.sequence() == *sequence,
+            JMDictEntryId::Reading(_) => entry
+                .readings()
+                .iter()
+                .any(|r| self.match_reading(r.string())),
+        }
+    }
     pub fn into_owned(self) -> JMDictEntryId<'static> {
         match self {
             JMDictEntryId::Kanji(my_kanji_string) => {
                 JMDictEntryId::Kanji(Cow::Owned(my_kanji_string.into_owned()))
             }
+            JMDictEntryId::Sequence(sequence) => JMDictEntryId::Sequence(sequence),
+            JMDictEntryId::Reading(my_reading_string) => {
+                JMDictEntryId::Reading(Cow::Owned(my_reading_string.into_owned()))
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_word() {
+        assert_eq!(
+            JMDictEntryId::from_word("食べる"),
+            JMDictEntryId::from_kanji("食べる")
+        );
+        assert_eq!(
+            JMDictEntryId::from_word("たべる"),
+            JMDictEntryId::from_reading("たべる")
+        );
+        assert_eq!(
+            JMDictEntryId::from_word("タベル"),
+            JMDictEntryId::from_reading("タベル")
+        );
+        assert_eq!(
+            JMDictEntryId::from_word("taberu"),
+            JMDictEntryId::from_reading("taberu")
+        );
+        assert_eq!(
+            JMDictEntryId::from_word("ＮＨＫ"),
+            JMDictEntryId::from_kanji("ＮＨＫ")
+        );
+        assert_eq!(JMDictEntryId::from_word(""), JMDictEntryId::from_kanji(""));
+    }
+
+    #[test]
+    fn match_reading() {
+        assert!(JMDictEntryId::from_word("taberu").match_reading("たべる"));
+        assert!(JMDictEntryId::from_word("tōkyō").match_reading("とうきょう"));
+        assert!(JMDictEntryId::from_word("ﾀﾍﾞﾙ").match_reading("たべる"));
+        assert!(!JMDictEntryId::from_word("taberu").match_reading("たべた"));
+        assert!(!JMDictEntryId::from_word("taberu").match_kanji("食べる"));
+    }
+}
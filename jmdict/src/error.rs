@@ -0,0 +1,116 @@
+#[derive(Debug)]
+pub enum Error {
+    IoError(std::io::Error),
+    /// Malformed XML, with the byte position in the decompressed file and the element being read
+    #[cfg(feature = "reader")]
+    XmlError {
+        position: usize,
+        element: String,
+        error: quick_xml::Error,
+    },
+    /// Well-formed XML with invalid contents, such as a non-numeric sequence number
+    InvalidData {
+        position: usize,
+        element: String,
+        message: String,
+    },
+    /// The file ended in the middle of an element
+    UnexpectedEof {
+        position: usize,
+        element: String,
+    },
+    /// Compressed file in a format whose feature is not enabled, such as xz without `xz2`
+    #[cfg(feature = "reader")]
+    UnsupportedCompression(crate::compression::Compression),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::IoError(error)
+    }
+}
+
+impl Error {
+    #[cfg(feature = "reader")]
+    pub fn xml(position: usize, element: &[u8], error: quick_xml::Error) -> Self {
+        Error::XmlError {
+            position,
+            element: String::from_utf8_lossy(element).into_owned(),
+            error,
+        }
+    }
+    pub fn invalid_data<S: Into<String>>(position: usize, element: &[u8], message: S) -> Self {
+        Error::InvalidData {
+            position,
+            element: String::from_utf8_lossy(element).into_owned(),
+            message: message.into(),
+        }
+    }
+    pub fn unexpected_eof(position: usize, element: &[u8]) -> Self {
+        Error::UnexpectedEof {
+            position,
+            element: String::from_utf8_lossy(element).into_owned(),
+        }
+    }
+    /// Byte position of the error in the decompressed dictionary file, if known.
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            Error::IoError(_) => None,
+            #[cfg(feature = "reader")]
+            Error::XmlError { position, .. } => Some(*position),
+            Error::InvalidData { position, .. } => Some(*position),
+            Error::UnexpectedEof { position, .. } => Some(*position),
+            #[cfg(feature = "reader")]
+            Error::UnsupportedCompression(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::IoError(e) => write!(f, "I/O error: {}", e),
+            #[cfg(feature = "reader")]
+            Error::XmlError {
+                position,
+                element,
+                error,
+            } => write!(
+                f,
+                "XML error at position {} in <{}>: {}",
+                position, element, error
+            ),
+            Error::InvalidData {
+                position,
+                element,
+                message,
+            } => write!(
+                f,
+                "Invalid data at position {} in <{}>: {}",
+                position, element, message
+            ),
+            Error::UnexpectedEof { position, element } => write!(
+                f,
+                "Unexpected end of file at position {} in <{}>",
+                position, element
+            ),
+            #[cfg(feature = "reader")]
+            Error::UnsupportedCompression(compression) => write!(
+                f,
+                "Unsupported {} compressed file, enable the {} feature of jmdict to read it",
+                compression.name(),
+                compression.feature().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IoError(e) => Some(e),
+            // quick_xml::Error does not implement std::error::Error
+            _ => None,
+        }
+    }
+}
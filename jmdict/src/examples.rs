@@ -0,0 +1,329 @@
+//! Example sentences from the Tanaka corpus, as distributed by Tatoeba in the `examples.utf` file.
+//!
+//! Each sentence comes as a pair of lines: an `A:` line with the Japanese sentence, its English
+//! translation and a Tatoeba identifier, and a `B:` line listing the dictionary form of its words,
+//! such as `彼(かれ)[01]{彼の}~`.
+
+use super::dictionary::JMDict;
+use super::entry::JMDictEntry;
+use super::kana;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::BufRead;
+
+/// Word of an example sentence, from its `B:` line.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ExampleWord {
+    headword: String,
+    reading: Option<String>,
+    sense: Option<u32>,
+    form: Option<String>,
+    checked: bool,
+}
+
+impl ExampleWord {
+    /// Parse a word such as `彼(かれ)[01]{彼の}~`.
+    pub fn parse(word_string: &str) -> Option<Self> {
+        let checked = word_string.ends_with('~');
+        let word_string = word_string.trim_end_matches('~');
+        let headword_end = word_string
+            .find(['(', '[', '{'])
+            .unwrap_or(word_string.len());
+        let headword = &word_string[..headword_end];
+        if headword.is_empty() {
+            return None;
+        }
+        let delimited = |start: char, end: char| {
+            let part_start = word_string[headword_end..].find(start)? + headword_end + 1;
+            let part_end = word_string[part_start..].find(end)? + part_start;
+            Some(&word_string[part_start..part_end])
+        };
+        Some(ExampleWord {
+            headword: String::from(headword),
+            reading: delimited('(', ')').map(String::from),
+            sense: delimited('[', ']').and_then(|sense| sense.parse().ok()),
+            form: delimited('{', '}').map(String::from),
+            checked,
+        })
+    }
+    /// Dictionary form of the word, in kanji or kana.
+    pub fn headword(&self) -> &str {
+        &self.headword
+    }
+    /// Reading of the headword, given when it has several.
+    pub fn reading(&self) -> Option<&str> {
+        self.reading.as_ref().map(|s| s as &str)
+    }
+    /// Number of the sense used in the sentence, starting at 1.
+    pub fn sense(&self) -> Option<u32> {
+        self.sense
+    }
+    /// Form of the word as it appears in the sentence, if it differs from the headword.
+    pub fn form(&self) -> Option<&str> {
+        self.form.as_ref().map(|s| s as &str)
+    }
+    /// Whether the sentence has been checked as a good example of the word.
+    pub fn checked(&self) -> bool {
+        self.checked
+    }
+}
+
+/// Japanese sentence with its English translation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ExampleSentence {
+    japanese: String,
+    english: String,
+    words: Vec<ExampleWord>,
+}
+
+impl ExampleSentence {
+    /// Parse a pair of lines, without their `A: ` and `B: ` prefixes.
+    ///
+    /// The `#ID=` Tatoeba identifier at the end of the translation is dropped.
+    pub fn parse(a_line: &str, b_line: &str) -> Option<Self> {
+        let mut parts = a_line.splitn(2, '\t');
+        let japanese = parts.next()?.trim();
+        let english = parts.next()?;
+        let english = english.split("#ID=").next().unwrap_or(english).trim();
+        Some(ExampleSentence {
+            japanese: String::from(japanese),
+            english: String::from(english),
+            words: b_line
+                .split_whitespace()
+                .filter_map(ExampleWord::parse)
+                .collect(),
+        })
+    }
+    pub fn japanese(&self) -> &str {
+        &self.japanese
+    }
+    pub fn english(&self) -> &str {
+        &self.english
+    }
+    pub fn words(&self) -> &[ExampleWord] {
+        &self.words
+    }
+}
+
+/// Read every sentence of an `examples.utf` file.
+///
+/// Lines other than `A:`/`B:` pairs are skipped.
+pub fn read_examples<R: BufRead>(buf_reader: R) -> Result<Vec<ExampleSentence>, crate::Error> {
+    const A_PREFIX: &str = "A: ";
+    const B_PREFIX: &str = "B: ";
+    let mut sentences: Vec<ExampleSentence> = Vec::new();
+    let mut a_line: Option<String> = None;
+    for line in buf_reader.lines() {
+        let line = line?;
+        if let Some(a_text) = line.strip_prefix(A_PREFIX) {
+            a_line = Some(String::from(a_text));
+        } else if let Some(b_text) = line.strip_prefix(B_PREFIX) {
+            let sentence = a_line
+                .take()
+                .and_then(|a_line| ExampleSentence::parse(&a_line, b_text));
+            if let Some(sentence) = sentence {
+                sentences.push(sentence);
+            }
+        }
+    }
+    Ok(sentences)
+}
+
+/// Whether an entry is the dictionary entry of an example word.
+fn matches_word(entry: &JMDictEntry, word: &ExampleWord) -> bool {
+    match word.reading() {
+        Some(reading) => {
+            let reading = kana::normalize(reading);
+            entry
+                .readings()
+                .iter()
+                .any(|r| kana::normalize(r.string()) == reading)
+        }
+        None => true,
+    }
+}
+
+/// Sentence using an entry, as indexed by `ExampleIndex`.
+#[derive(Debug, Clone, Copy)]
+struct ExampleLink {
+    sentence_index: usize,
+    checked: bool,
+}
+
+/// Example sentences linked to the dictionary entries of their words.
+#[derive(Debug, Default, Clone)]
+pub struct ExampleIndex {
+    sentences: Vec<ExampleSentence>,
+    sequence_index: HashMap<u32, Vec<ExampleLink>>,
+}
+
+impl ExampleIndex {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Link sentences to the entries of their words.
+    ///
+    /// A word whose headword and reading match several entries is linked to the most common one.
+    pub fn from_sentences<I: IntoIterator<Item = ExampleSentence>>(
+        sentences: I,
+        jmdict: &JMDict,
+    ) -> Self {
+        let mut example_index = Self::new();
+        for sentence in sentences {
+            example_index.add_sentence(sentence, jmdict);
+        }
+        example_index
+    }
+    pub fn add_sentence(&mut self, sentence: ExampleSentence, jmdict: &JMDict) {
+        let index = self.sentences.len();
+        for word in sentence.words() {
+            let entry = jmdict
+                .by_kanji(word.headword())
+                .chain(jmdict.by_reading(word.headword()))
+                .filter(|entry| matches_word(entry, word))
+                .fold(None, |best: Option<&JMDictEntry>, entry| match best {
+                    Some(best) if best.priority_score() >= entry.priority_score() => Some(best),
+                    _ => Some(entry),
+                });
+            if let Some(entry) = entry {
+                let links = self.sequence_index.entry(entry.sequence()).or_default();
+                match links.last_mut() {
+                    Some(link) if link.sentence_index == index => link.checked |= word.checked(),
+                    _ => links.push(ExampleLink {
+                        sentence_index: index,
+                        checked: word.checked(),
+                    }),
+                }
+            }
+        }
+        self.sentences.push(sentence);
+    }
+    pub fn len(&self) -> usize {
+        self.sentences.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.sentences.is_empty()
+    }
+    /// Up to `limit` sentences using an entry, those checked as good examples of it first.
+    pub fn examples_for(&self, sequence: u32, limit: usize) -> Vec<&ExampleSentence> {
+        let mut links: Vec<ExampleLink> = self
+            .sequence_index
+            .get(&sequence)
+            .cloned()
+            .unwrap_or_default();
+        // stable sort, keeping the file order between checked and unchecked sentences
+        links.sort_by_key(|link| !link.checked);
+        links
+            .into_iter()
+            .take(limit)
+            .map(|link| &self.sentences[link.sentence_index])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MUIRIEL_A: &str = "ムーリエルは２０歳になりました。\tMuiriel is 20 now.#ID=1282_4707";
+    const MUIRIEL_B: &str = "は 二十歳(はたち){２０歳} になる[01]{になりました}";
+    const NAP_A: &str =
+        "すぐに諦めて昼寝をするかも知れない。\tI may give up soon and just nap instead.#ID=1300_4727";
+    const NAP_B: &str = "直ぐに{すぐに} 諦める{諦めて} 昼寝 を 為る(する){する} かも知れない";
+
+    fn word(
+        headword: &str,
+        reading: Option<&str>,
+        sense: Option<u32>,
+        form: Option<&str>,
+        checked: bool,
+    ) -> ExampleWord {
+        ExampleWord {
+            headword: String::from(headword),
+            reading: reading.map(String::from),
+            sense,
+            form: form.map(String::from),
+            checked,
+        }
+    }
+
+    #[test]
+    fn parse_word() {
+        assert_eq!(
+            ExampleWord::parse("は"),
+            Some(word("は", None, None, None, false))
+        );
+        assert_eq!(
+            ExampleWord::parse("二十歳(はたち){２０歳}"),
+            Some(word("二十歳", Some("はたち"), None, Some("２０歳"), false))
+        );
+        assert_eq!(
+            ExampleWord::parse("になる[01]{になりました}"),
+            Some(word("になる", None, Some(1), Some("になりました"), false))
+        );
+        assert_eq!(
+            ExampleWord::parse("彼(かれ)[01]{彼の}~"),
+            Some(word("彼", Some("かれ"), Some(1), Some("彼の"), true))
+        );
+        assert_eq!(
+            ExampleWord::parse("昼寝~"),
+            Some(word("昼寝", None, None, None, true))
+        );
+        // a sense number that is not a number is ignored
+        assert_eq!(
+            ExampleWord::parse("昼寝[x]"),
+            Some(word("昼寝", None, None, None, false))
+        );
+        assert_eq!(ExampleWord::parse("(はたち)"), None);
+        assert_eq!(ExampleWord::parse("~"), None);
+    }
+
+    #[test]
+    fn parse_sentence() {
+        let sentence = ExampleSentence::parse(MUIRIEL_A, MUIRIEL_B).unwrap();
+        assert_eq!(sentence.japanese(), "ムーリエルは２０歳になりました。");
+        assert_eq!(sentence.english(), "Muiriel is 20 now.");
+        assert_eq!(
+            sentence.words(),
+            &[
+                word("は", None, None, None, false),
+                word("二十歳", Some("はたち"), None, Some("２０歳"), false),
+                word("になる", None, Some(1), Some("になりました"), false),
+            ][..]
+        );
+
+        let sentence = ExampleSentence::parse(NAP_A, NAP_B).unwrap();
+        assert_eq!(
+            sentence.english(),
+            "I may give up soon and just nap instead."
+        );
+        let headwords: Vec<&str> = sentence.words().iter().map(|w| w.headword()).collect();
+        assert_eq!(
+            headwords,
+            vec!["直ぐに", "諦める", "昼寝", "を", "為る", "かも知れない"]
+        );
+        assert_eq!(sentence.words()[4].reading(), Some("する"));
+        assert_eq!(sentence.words()[4].form(), Some("する"));
+
+        // the translation follows a tab
+        assert_eq!(
+            ExampleSentence::parse("ムーリエルは２０歳になりました。", MUIRIEL_B),
+            None
+        );
+    }
+
+    #[test]
+    fn read_example_file() {
+        let file = format!(
+            "# comment\nA: {}\nB: {}\nB: は\nA: {}\nB: {}\n",
+            MUIRIEL_A, MUIRIEL_B, NAP_A, NAP_B
+        );
+        let sentences = read_examples(file.as_bytes()).unwrap();
+        assert_eq!(sentences.len(), 2);
+        assert_eq!(sentences[0].words().len(), 3);
+        assert_eq!(
+            sentences[1].japanese(),
+            "すぐに諦めて昼寝をするかも知れない。"
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! Alignment of readings to the kanji of a spelling, for furigana such as 食【た】べる.
+
+use super::kana;
+use super::kanji::Kanjidic;
+use serde::{Deserialize, Serialize};
+
+const UNVOICED_KANA: &str = "かきくけこさしすせそたちつてとはひふへほ";
+const VOICED_KANA: &str = "がぎぐげござじずぜぞだぢづでどばびぶべぼ";
+const SEMI_VOICED_KANA: &str = "ぱぴぷぺぽ";
+const ITERATION_MARK: char = '々';
+
+/// Part of a spelling along with its reading, if it needs one.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FuriganaSegment {
+    text: String,
+    reading: Option<String>,
+}
+
+impl FuriganaSegment {
+    fn new(text: String, reading: Option<String>) -> Self {
+        FuriganaSegment { text, reading }
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    /// Reading of the text, or `None` for kana written as is.
+    pub fn reading(&self) -> Option<&str> {
+        self.reading.as_ref().map(|s| s as &str)
+    }
+}
+
+/// Whether a character of a spelling needs a reading: anything but kana, except the small ヶ and
+/// ヵ read か, が or こ.
+fn needs_reading(c: char) -> bool {
+    !kana::is_kana(c) || c == 'ヶ' || c == 'ヵ'
+}
+
+/// Split a spelling into runs of characters needing a reading and runs of kana.
+fn spelling_runs(spelling: &str) -> Vec<(Vec<char>, bool)> {
+    let mut runs: Vec<(Vec<char>, bool)> = Vec::new();
+    for c in spelling.chars() {
+        let annotated = needs_reading(c);
+        match runs.last_mut() {
+            Some((run, run_annotated)) if *run_annotated == annotated => run.push(c),
+            _ => runs.push((vec![c], annotated)),
+        }
+    }
+    runs
+}
+
+/// Hiragana characters of a text, one for each of its characters.
+fn hiragana_chars(text: &str) -> Vec<char> {
+    kana::katakana_to_hiragana(text).chars().collect()
+}
+
+/// Readings of the annotated runs, as lengths in the reading, from the run at `run_index` on.
+///
+/// Kana runs have to appear as is in the reading; annotated runs take the shortest reading that
+/// lets the rest of the spelling match.
+fn match_runs(
+    runs: &[(Vec<char>, bool)],
+    run_index: usize,
+    reading: &[char],
+    position: usize,
+) -> Option<Vec<usize>> {
+    let (run, annotated) = match runs.get(run_index) {
+        Some(run) => run,
+        None if position == reading.len() => return Some(Vec::new()),
+        None => return None,
+    };
+    if !annotated {
+        let run_hiragana = hiragana_chars(&run.iter().collect::<String>());
+        let run_end = position + run_hiragana.len();
+        if run_end > reading.len() || reading[position..run_end] != run_hiragana[..] {
+            return None;
+        }
+        return match_runs(runs, run_index + 1, reading, run_end);
+    }
+    for end in position + 1..=reading.len() {
+        if let Some(mut lengths) = match_runs(runs, run_index + 1, reading, end) {
+            lengths.insert(0, end - position);
+            return Some(lengths);
+        }
+    }
+    None
+}
+
+/// Align a reading to a spelling, giving the reading of each run of kanji between the kana.
+///
+/// For example 食べる read たべる gives 食 read た and べる. Runs of several kanji, such as 学校,
+/// keep a single reading. Returns `None` if the kana of the spelling do not match the reading.
+pub fn align(spelling: &str, reading: &str) -> Option<Vec<FuriganaSegment>> {
+    let runs = spelling_runs(spelling);
+    let reading_chars: Vec<char> = reading.chars().collect();
+    let reading_hiragana = hiragana_chars(reading);
+    let mut lengths = match_runs(&runs, 0, &reading_hiragana, 0)?.into_iter();
+    let mut position = 0;
+    let mut segments: Vec<FuriganaSegment> = Vec::with_capacity(runs.len());
+    for (run, annotated) in runs {
+        let length = if annotated {
+            lengths.next()?
+        } else {
+            run.len()
+        };
+        let run_reading: String = reading_chars[position..position + length].iter().collect();
+        position += length;
+        let run_text: String = run.into_iter().collect();
+        segments.push(if annotated {
+            FuriganaSegment::new(run_text, Some(run_reading))
+        } else {
+            FuriganaSegment::new(run_text, None)
+        });
+    }
+    Some(segments)
+}
+
+/// Possible readings of a kanji in a compound, in hiragana, from its KANJIDIC2 readings.
+///
+/// Voiced initial sounds (rendaku, as in 本棚 ほんだな) and final sounds shortened to a small っ
+/// (as in 学校 がっこう) are included.
+fn compound_readings(kanjidic: &Kanjidic, literal: char) -> Vec<Vec<char>> {
+    let kanji = match kanjidic.get(literal) {
+        Some(kanji) => kanji,
+        None => return Vec::new(),
+    };
+    let base_readings = kanji
+        .on_readings()
+        .iter()
+        .chain(kanji.kun_readings())
+        .map(|r| {
+            // okurigana follow a dot, and prefixes or suffixes are marked with a dash
+            let stem = r.split('.').next().unwrap_or(r).trim_matches('-');
+            hiragana_chars(stem)
+        });
+    let mut readings: Vec<Vec<char>> = Vec::new();
+    for base_reading in base_readings.filter(|r| !r.is_empty()) {
+        let mut variants = vec![base_reading.clone()];
+        if let Some(unvoiced_index) = UNVOICED_KANA.chars().position(|c| c == base_reading[0]) {
+            let voiced = VOICED_KANA.chars().nth(unvoiced_index);
+            let semi_voiced = unvoiced_index
+                .checked_sub(15)
+                .and_then(|i| SEMI_VOICED_KANA.chars().nth(i));
+            for initial in voiced.into_iter().chain(semi_voiced) {
+                let mut variant = base_reading.clone();
+                variant[0] = initial;
+                variants.push(variant);
+            }
+        }
+        if base_reading.len() > 1 {
+            match base_reading[base_reading.len() - 1] {
+                'つ' | 'く' | 'ち' | 'き' => {
+                    let mut variant = base_reading.clone();
+                    *variant.last_mut().unwrap() = 'っ';
+                    variants.push(variant);
+                }
+                _ => (),
+            }
+        }
+        for variant in variants {
+            if !readings.contains(&variant) {
+                readings.push(variant);
+            }
+        }
+    }
+    readings
+}
+
+/// Readings of each kanji of a run, as lengths in the reading, from the kanji at `kanji_index` on.
+fn match_characters(
+    candidates: &[Vec<Vec<char>>],
+    kanji_index: usize,
+    reading: &[char],
+    position: usize,
+) -> Option<Vec<usize>> {
+    let kanji_candidates = match candidates.get(kanji_index) {
+        Some(kanji_candidates) => kanji_candidates,
+        None if position == reading.len() => return Some(Vec::new()),
+        None => return None,
+    };
+    for candidate in kanji_candidates {
+        let end = position + candidate.len();
+        if end > reading.len() || reading[position..end] != candidate[..] {
+            continue;
+        }
+        if let Some(mut lengths) = match_characters(candidates, kanji_index + 1, reading, end) {
+            lengths.insert(0, candidate.len());
+            return Some(lengths);
+        }
+    }
+    None
+}
+
+/// Split the reading of a run of kanji between its characters, using their KANJIDIC2 readings.
+fn split_run(segment: &FuriganaSegment, kanjidic: &Kanjidic) -> Option<Vec<FuriganaSegment>> {
+    let reading = segment.reading()?;
+    let literals: Vec<char> = segment.text().chars().collect();
+    let mut candidates: Vec<Vec<Vec<char>>> = Vec::with_capacity(literals.len());
+    for (index, &literal) in literals.iter().enumerate() {
+        // 々 repeats the previous kanji, possibly voiced
+        let literal = if literal == ITERATION_MARK && index > 0 {
+            literals[index - 1]
+        } else {
+            literal
+        };
+        candidates.push(compound_readings(kanjidic, literal));
+    }
+    let reading_chars: Vec<char> = reading.chars().collect();
+    let lengths = match_characters(&candidates, 0, &hiragana_chars(reading), 0)?;
+    let mut position = 0;
+    let segments = literals
+        .into_iter()
+        .zip(lengths)
+        .map(|(literal, length)| {
+            let kanji_reading: String = reading_chars[position..position + length].iter().collect();
+            position += length;
+            FuriganaSegment::new(literal.to_string(), Some(kanji_reading))
+        })
+        .collect();
+    Some(segments)
+}
+
+/// Align a reading to each kanji of a spelling, such as 学 read がっ and 校 read こう.
+///
+/// Runs of kanji whose reading cannot be split from the KANJIDIC2 readings of their characters,
+/// such as 今日 read きょう, keep a single reading.
+pub fn align_characters(
+    spelling: &str,
+    reading: &str,
+    kanjidic: &Kanjidic,
+) -> Option<Vec<FuriganaSegment>> {
+    let mut segments: Vec<FuriganaSegment> = Vec::new();
+    for segment in align(spelling, reading)? {
+        if segment.reading().is_some() && segment.text().chars().count() > 1 {
+            if let Some(kanji_segments) = split_run(&segment, kanjidic) {
+                segments.extend(kanji_segments);
+                continue;
+            }
+        }
+        segments.push(segment);
+    }
+    Some(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kanji::KanjiCharacter;
+
+    fn kanji_character(
+        literal: char,
+        on_readings: &[&str],
+        kun_readings: &[&str],
+    ) -> KanjiCharacter {
+        let mut character = KanjiCharacter::new(literal);
+        for &reading in on_readings {
+            character.add_on_reading(String::from(reading));
+        }
+        for &reading in kun_readings {
+            character.add_kun_reading(String::from(reading));
+        }
+        character
+    }
+
+    /// Readings of a few characters, as given by KANJIDIC2.
+    fn kanjidic() -> Kanjidic {
+        Kanjidic::from_characters(vec![
+            kanji_character('漢', &["カン"], &[]),
+            kanji_character('字', &["ジ"], &["あざ", "あざな", "-な"]),
+            kanji_character(
+                '食',
+                &["ショク", "ジキ"],
+                &["く.う", "く.らう", "た.べる", "は.む"],
+            ),
+            kanji_character('学', &["ガク"], &["まな.ぶ"]),
+            kanji_character('校', &["コウ", "キョウ"], &[]),
+            kanji_character('人', &["ジン", "ニン"], &["ひと", "-り", "-と"]),
+            kanji_character('今', &["コン", "キン"], &["いま"]),
+            kanji_character('日', &["ニチ", "ジツ"], &["ひ", "-び", "-か"]),
+        ])
+    }
+
+    fn segments(aligned: Option<Vec<FuriganaSegment>>) -> Vec<(String, Option<String>)> {
+        aligned
+            .expect("reading should align")
+            .into_iter()
+            .map(|segment| (segment.text, segment.reading))
+            .collect()
+    }
+
+    fn segment(text: &str, reading: Option<&str>) -> (String, Option<String>) {
+        (String::from(text), reading.map(String::from))
+    }
+
+    #[test]
+    fn align_kanji_run() {
+        assert_eq!(
+            segments(align("漢字", "かんじ")),
+            vec![segment("漢字", Some("かんじ"))]
+        );
+        assert_eq!(
+            segments(align_characters("漢字", "かんじ", &kanjidic())),
+            vec![segment("漢", Some("かん")), segment("字", Some("じ"))]
+        );
+    }
+
+    #[test]
+    fn align_okurigana() {
+        let expected = vec![segment("食", Some("た")), segment("べる", None)];
+        assert_eq!(segments(align("食べる", "たべる")), expected);
+        assert_eq!(
+            segments(align_characters("食べる", "たべる", &kanjidic())),
+            expected
+        );
+        // kana of the spelling match the reading regardless of script
+        assert_eq!(
+            segments(align("食べる", "タベル")),
+            vec![segment("食", Some("タ")), segment("べる", None)]
+        );
+    }
+
+    #[test]
+    fn align_gemination() {
+        assert_eq!(
+            segments(align_characters("学校", "がっこう", &kanjidic())),
+            vec![segment("学", Some("がっ")), segment("校", Some("こう"))]
+        );
+    }
+
+    #[test]
+    fn align_rendaku() {
+        assert_eq!(
+            segments(align_characters("人々", "ひとびと", &kanjidic())),
+            vec![segment("人", Some("ひと")), segment("々", Some("びと"))]
+        );
+    }
+
+    #[test]
+    fn align_mismatch() {
+        assert_eq!(align("食べる", "たべない"), None);
+        assert_eq!(align("食べる", "べる"), None);
+        assert_eq!(align_characters("食べる", "たべない", &kanjidic()), None);
+        // a run whose reading cannot be split between its kanji keeps a single reading
+        assert_eq!(
+            segments(align_characters("今日", "きょう", &kanjidic())),
+            vec![segment("今日", Some("きょう"))]
+        );
+        // and so does a run of kanji missing from the dictionary
+        assert_eq!(
+            segments(align_characters("漢字", "かんじ", &Kanjidic::new())),
+            vec![segment("漢字", Some("かんじ"))]
+        );
+    }
+}
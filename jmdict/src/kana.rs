@@ -0,0 +1,289 @@
+//! Kana normalization and romaji conversion, so that words can be looked up regardless of kana
+//! script, character width or input method.
+
+use std::collections::HashMap;
+
+/// Full-width forms of the half-width katakana block, from U+FF61 to U+FF9F.
+const HALF_WIDTH_KATAKANA: &str =
+    "。「」、・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン゛゜";
+const HALF_WIDTH_DAKUTEN: char = '\u{FF9E}';
+const HALF_WIDTH_HANDAKUTEN: char = '\u{FF9F}';
+
+/// Whether a character is hiragana, katakana, or half-width katakana.
+pub fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3041}'..='\u{309F}' | '\u{30A0}'..='\u{30FF}' | '\u{FF66}'..='\u{FF9F}')
+}
+
+pub fn is_hiragana(c: char) -> bool {
+    matches!(c, '\u{3041}'..='\u{309F}')
+}
+
+pub fn is_katakana(c: char) -> bool {
+    matches!(c, '\u{30A0}'..='\u{30FF}' | '\u{FF66}'..='\u{FF9F}')
+}
+
+/// Convert katakana to hiragana, leaving other characters such as `ー` untouched.
+pub fn katakana_to_hiragana(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{30A1}'..='\u{30F6}' | '\u{30FD}'..='\u{30FE}' => {
+                std::char::from_u32(c as u32 - 0x60).unwrap_or(c)
+            }
+            c => c,
+        })
+        .collect()
+}
+
+/// Convert hiragana to katakana, leaving other characters untouched.
+pub fn hiragana_to_katakana(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{3041}'..='\u{3096}' | '\u{309D}'..='\u{309E}' => {
+                std::char::from_u32(c as u32 + 0x60).unwrap_or(c)
+            }
+            c => c,
+        })
+        .collect()
+}
+
+/// Katakana with a voiced sound mark applied, such as `ガ` for `カ`.
+fn voiced_katakana(c: char) -> Option<char> {
+    match c {
+        'ウ' => Some('ヴ'),
+        'カ' | 'キ' | 'ク' | 'ケ' | 'コ' | 'サ' | 'シ' | 'ス' | 'セ' | 'ソ' | 'タ' | 'チ'
+        | 'ツ' | 'テ' | 'ト' | 'ハ' | 'ヒ' | 'フ' | 'ヘ' | 'ホ' => {
+            std::char::from_u32(c as u32 + 1)
+        }
+        _ => None,
+    }
+}
+
+/// Katakana with a semi-voiced sound mark applied, such as `パ` for `ハ`.
+fn semi_voiced_katakana(c: char) -> Option<char> {
+    match c {
+        'ハ' | 'ヒ' | 'フ' | 'ヘ' | 'ホ' => std::char::from_u32(c as u32 + 2),
+        _ => None,
+    }
+}
+
+/// Convert half-width katakana to full-width katakana, and full-width ASCII to ASCII.
+///
+/// Half-width sound marks are combined with the preceding kana when possible.
+pub fn normalize_width(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    let mut previous: Option<char> = None;
+    for c in text.chars() {
+        let c = match c {
+            '\u{FF01}'..='\u{FF5E}' => std::char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            '\u{3000}' => ' ',
+            '\u{FF61}'..='\u{FF9F}' => {
+                let combined = match c {
+                    HALF_WIDTH_DAKUTEN => previous.and_then(voiced_katakana),
+                    HALF_WIDTH_HANDAKUTEN => previous.and_then(semi_voiced_katakana),
+                    _ => None,
+                };
+                if let Some(combined) = combined {
+                    normalized.pop();
+                    normalized.push(combined);
+                    previous = Some(combined);
+                    continue;
+                }
+                HALF_WIDTH_KATAKANA
+                    .chars()
+                    .nth((c as u32 - 0xFF61) as usize)
+                    .unwrap_or(c)
+            }
+            c => c,
+        };
+        normalized.push(c);
+        previous = Some(c);
+    }
+    normalized
+}
+
+/// Normalize a kanji or kana string for comparison: full-width kana, ASCII alphanumerics, and
+/// hiragana instead of katakana.
+pub fn normalize(text: &str) -> String {
+    katakana_to_hiragana(&normalize_width(text))
+}
+
+/// Romaji syllables and their hiragana, covering Hepburn, Kunrei-shiki and common input method
+/// spellings.
+#[rustfmt::skip]
+const ROMAJI_SYLLABLES: &[(&str, &str)] = &[
+    ("a", "あ"), ("i", "い"), ("u", "う"), ("e", "え"), ("o", "お"),
+    ("ka", "か"), ("ki", "き"), ("ku", "く"), ("ke", "け"), ("ko", "こ"),
+    ("kya", "きゃ"), ("kyu", "きゅ"), ("kyo", "きょ"),
+    ("ga", "が"), ("gi", "ぎ"), ("gu", "ぐ"), ("ge", "げ"), ("go", "ご"),
+    ("gya", "ぎゃ"), ("gyu", "ぎゅ"), ("gyo", "ぎょ"),
+    ("sa", "さ"), ("shi", "し"), ("si", "し"), ("su", "す"), ("se", "せ"), ("so", "そ"),
+    ("sha", "しゃ"), ("shu", "しゅ"), ("sho", "しょ"), ("she", "しぇ"),
+    ("sya", "しゃ"), ("syu", "しゅ"), ("syo", "しょ"),
+    ("za", "ざ"), ("ji", "じ"), ("zi", "じ"), ("zu", "ず"), ("ze", "ぜ"), ("zo", "ぞ"),
+    ("ja", "じゃ"), ("ju", "じゅ"), ("jo", "じょ"), ("je", "じぇ"),
+    ("jya", "じゃ"), ("jyu", "じゅ"), ("jyo", "じょ"),
+    ("zya", "じゃ"), ("zyu", "じゅ"), ("zyo", "じょ"),
+    ("ta", "た"), ("chi", "ち"), ("ti", "ち"), ("tsu", "つ"), ("tu", "つ"), ("te", "て"), ("to", "と"),
+    ("cha", "ちゃ"), ("chu", "ちゅ"), ("cho", "ちょ"), ("che", "ちぇ"),
+    ("tya", "ちゃ"), ("tyu", "ちゅ"), ("tyo", "ちょ"),
+    ("cya", "ちゃ"), ("cyu", "ちゅ"), ("cyo", "ちょ"),
+    ("thi", "てぃ"), ("dhi", "でぃ"), ("twu", "とぅ"),
+    ("da", "だ"), ("di", "ぢ"), ("du", "づ"), ("dzu", "づ"), ("de", "で"), ("do", "ど"),
+    ("dya", "ぢゃ"), ("dyu", "ぢゅ"), ("dyo", "ぢょ"),
+    ("na", "な"), ("ni", "に"), ("nu", "ぬ"), ("ne", "ね"), ("no", "の"),
+    ("nya", "にゃ"), ("nyu", "にゅ"), ("nyo", "にょ"),
+    ("ha", "は"), ("hi", "ひ"), ("fu", "ふ"), ("hu", "ふ"), ("he", "へ"), ("ho", "ほ"),
+    ("hya", "ひゃ"), ("hyu", "ひゅ"), ("hyo", "ひょ"),
+    ("fa", "ふぁ"), ("fi", "ふぃ"), ("fe", "ふぇ"), ("fo", "ふぉ"),
+    ("ba", "ば"), ("bi", "び"), ("bu", "ぶ"), ("be", "べ"), ("bo", "ぼ"),
+    ("bya", "びゃ"), ("byu", "びゅ"), ("byo", "びょ"),
+    ("pa", "ぱ"), ("pi", "ぴ"), ("pu", "ぷ"), ("pe", "ぺ"), ("po", "ぽ"),
+    ("pya", "ぴゃ"), ("pyu", "ぴゅ"), ("pyo", "ぴょ"),
+    ("ma", "ま"), ("mi", "み"), ("mu", "む"), ("me", "め"), ("mo", "も"),
+    ("mya", "みゃ"), ("myu", "みゅ"), ("myo", "みょ"),
+    ("ya", "や"), ("yu", "ゆ"), ("yo", "よ"),
+    ("ra", "ら"), ("ri", "り"), ("ru", "る"), ("re", "れ"), ("ro", "ろ"),
+    ("rya", "りゃ"), ("ryu", "りゅ"), ("ryo", "りょ"),
+    ("wa", "わ"), ("wi", "ゐ"), ("we", "ゑ"), ("wo", "を"),
+    ("va", "ゔぁ"), ("vi", "ゔぃ"), ("vu", "ゔ"), ("ve", "ゔぇ"), ("vo", "ゔぉ"),
+    ("xa", "ぁ"), ("xi", "ぃ"), ("xu", "ぅ"), ("xe", "ぇ"), ("xo", "ぉ"),
+    ("xya", "ゃ"), ("xyu", "ゅ"), ("xyo", "ょ"), ("xtu", "っ"), ("xtsu", "っ"), ("xwa", "ゎ"),
+    ("-", "ー"),
+];
+
+/// Longest romaji syllable, in characters.
+const ROMAJI_MAX_LENGTH: usize = 4;
+
+fn is_romaji_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+/// Expand Hepburn macrons and Kunrei-shiki circumflexes into two vowels, such as `ou` for `ō`.
+fn expand_long_vowels(romaji: &str) -> String {
+    let mut expanded = String::with_capacity(romaji.len() + 4);
+    for c in romaji.chars() {
+        match c {
+            'ā' | 'â' => expanded.push_str("aa"),
+            'ī' | 'î' => expanded.push_str("ii"),
+            'ū' | 'û' => expanded.push_str("uu"),
+            'ē' | 'ê' => expanded.push_str("ee"),
+            'ō' | 'ô' => expanded.push_str("ou"),
+            c => expanded.push(c),
+        }
+    }
+    expanded
+}
+
+/// Convert a romaji word, in Hepburn or Kunrei-shiki, to hiragana.
+///
+/// Returns `None` if the text is empty or is not entirely made of romaji syllables, such as an
+/// English word or an abbreviation.
+pub fn romaji_to_hiragana(romaji: &str) -> Option<String> {
+    lazy_static! {
+        static ref SYLLABLES: HashMap<&'static str, &'static str> =
+            ROMAJI_SYLLABLES.iter().cloned().collect();
+    }
+    let romaji = expand_long_vowels(&romaji.trim().to_lowercase());
+    if romaji.is_empty() {
+        return None;
+    }
+    let chars: Vec<char> = romaji.chars().collect();
+    let mut hiragana = String::with_capacity(romaji.len() * 2);
+    let mut index = 0usize;
+    'syllables: while index < chars.len() {
+        let c = chars[index];
+        let next = chars.get(index + 1).cloned();
+        // syllabic n: before a consonant, at the end, or spelled `n'` or `nn`
+        if c == 'n' && !next.is_some_and(|n| is_romaji_vowel(n) || n == 'y') {
+            hiragana.push('ん');
+            let after_next = chars.get(index + 2).cloned();
+            index += match next {
+                Some('\'') => 2,
+                Some('n') if !after_next.is_some_and(|n| is_romaji_vowel(n) || n == 'y') => 2,
+                _ => 1,
+            };
+            continue;
+        }
+        // geminate consonant, such as `kk` or Hepburn `tch`
+        if !is_romaji_vowel(c)
+            && c.is_ascii_alphabetic()
+            && (next == Some(c) || (c == 't' && next == Some('c')))
+        {
+            hiragana.push('っ');
+            index += 1;
+            continue;
+        }
+        let max_length = ROMAJI_MAX_LENGTH.min(chars.len() - index);
+        for length in (1..=max_length).rev() {
+            let syllable: String = chars[index..index + length].iter().collect();
+            if let Some(kana) = SYLLABLES.get(syllable.as_str()) {
+                hiragana.push_str(kana);
+                index += length;
+                continue 'syllables;
+            }
+        }
+        return None;
+    }
+    Some(hiragana)
+}
+
+/// Normalize a word typed by a user: romaji is converted to hiragana, and kana is normalized with
+/// [`normalize`](fn.normalize.html).
+pub fn normalize_word(word: &str) -> String {
+    romaji_to_hiragana(word).unwrap_or_else(|| normalize(word.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syllabic_n() {
+        assert_eq!(romaji_to_hiragana("konnichiha").unwrap(), "こんにちは");
+        assert_eq!(romaji_to_hiragana("kon'nichiha").unwrap(), "こんにちは");
+        assert_eq!(romaji_to_hiragana("kin'en").unwrap(), "きんえん");
+        assert_eq!(romaji_to_hiragana("kinen").unwrap(), "きねん");
+        assert_eq!(romaji_to_hiragana("hon").unwrap(), "ほん");
+        assert_eq!(romaji_to_hiragana("shinbun").unwrap(), "しんぶん");
+    }
+
+    #[test]
+    fn gemination() {
+        assert_eq!(romaji_to_hiragana("maccha").unwrap(), "まっちゃ");
+        assert_eq!(romaji_to_hiragana("matcha").unwrap(), "まっちゃ");
+        assert_eq!(romaji_to_hiragana("gakkou").unwrap(), "がっこう");
+    }
+
+    #[test]
+    fn long_vowels() {
+        assert_eq!(romaji_to_hiragana("tōkyō").unwrap(), "とうきょう");
+        assert_eq!(romaji_to_hiragana("tôkyô").unwrap(), "とうきょう");
+        assert_eq!(romaji_to_hiragana("Tōkyō").unwrap(), "とうきょう");
+    }
+
+    #[test]
+    fn kunrei_shiki() {
+        assert_eq!(romaji_to_hiragana("si").unwrap(), "し");
+        assert_eq!(romaji_to_hiragana("tu").unwrap(), "つ");
+        assert_eq!(romaji_to_hiragana("hu").unwrap(), "ふ");
+        assert_eq!(romaji_to_hiragana("sya").unwrap(), "しゃ");
+        assert_eq!(romaji_to_hiragana("tyotto").unwrap(), "ちょっと");
+    }
+
+    #[test]
+    fn not_romaji() {
+        assert_eq!(romaji_to_hiragana(""), None);
+        assert_eq!(romaji_to_hiragana("CD"), None);
+        assert_eq!(romaji_to_hiragana("食べる"), None);
+        assert_eq!(romaji_to_hiragana("たべる"), None);
+    }
+
+    #[test]
+    fn half_width() {
+        assert_eq!(normalize_width("ｶﾞｯｺｳ"), "ガッコウ");
+        assert_eq!(normalize_width("ﾊﾟﾝ"), "パン");
+        assert_eq!(normalize_width("ｱﾞ"), "ア\u{309B}");
+        assert_eq!(normalize_width("ＣＤ１"), "CD1");
+        assert_eq!(normalize("ｶﾞｯｺｳ"), "がっこう");
+    }
+}
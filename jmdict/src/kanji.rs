@@ -0,0 +1,165 @@
+//! Per-character kanji data from the KANJIDIC2 dictionary.
+
+use super::entry::Gloss;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Whether a character is a CJK ideograph, as found in KANJIDIC2.
+pub fn is_kanji(c: char) -> bool {
+    matches!(c, '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' | '\u{20000}'..='\u{2FA1F}')
+}
+
+/// Entry of the KANJIDIC2 dictionary, describing a single kanji.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct KanjiCharacter {
+    literal: char,
+    on_readings: Vec<String>,
+    kun_readings: Vec<String>,
+    nanori: Vec<String>,
+    meanings: Vec<Gloss>,
+    stroke_count: Option<u8>,
+    grade: Option<u8>,
+    jlpt: Option<u8>,
+    frequency: Option<u16>,
+    radical: Option<u8>,
+}
+
+impl KanjiCharacter {
+    pub fn new(literal: char) -> Self {
+        Self {
+            literal,
+            on_readings: Vec::new(),
+            kun_readings: Vec::new(),
+            nanori: Vec::new(),
+            meanings: Vec::new(),
+            stroke_count: None,
+            grade: None,
+            jlpt: None,
+            frequency: None,
+            radical: None,
+        }
+    }
+    /// The kanji itself, from `<literal>`.
+    pub fn literal(&self) -> char {
+        self.literal
+    }
+    /// On (Sino-Japanese) readings, in katakana.
+    pub fn on_readings(&self) -> &[String] {
+        &self.on_readings
+    }
+    pub fn add_on_reading(&mut self, reading: String) {
+        self.on_readings.push(reading);
+    }
+    /// Kun (native Japanese) readings, in hiragana, with okurigana after a `.` as in `た.べる`.
+    pub fn kun_readings(&self) -> &[String] {
+        &self.kun_readings
+    }
+    pub fn add_kun_reading(&mut self, reading: String) {
+        self.kun_readings.push(reading);
+    }
+    /// Readings used only in names, from `<nanori>`.
+    pub fn nanori(&self) -> &[String] {
+        &self.nanori
+    }
+    pub fn add_nanori(&mut self, reading: String) {
+        self.nanori.push(reading);
+    }
+    /// Meanings from `<meaning>`, in English unless a language is set.
+    pub fn meanings(&self) -> &[Gloss] {
+        &self.meanings
+    }
+    pub fn add_meaning(&mut self, meaning: Gloss) {
+        self.meanings.push(meaning);
+    }
+    pub fn stroke_count(&self) -> Option<u8> {
+        self.stroke_count
+    }
+    pub fn set_stroke_count(&mut self, stroke_count: u8) {
+        self.stroke_count = Some(stroke_count);
+    }
+    /// School grade in which the kanji is taught: 1 to 6 in elementary school, 8 for the other
+    /// jōyō kanji, 9 and 10 for jinmeiyō kanji.
+    pub fn grade(&self) -> Option<u8> {
+        self.grade
+    }
+    pub fn set_grade(&mut self, grade: u8) {
+        self.grade = Some(grade);
+    }
+    /// Level of the former Japanese Language Proficiency Test, from 4 (easiest) to 1.
+    pub fn jlpt(&self) -> Option<u8> {
+        self.jlpt
+    }
+    pub fn set_jlpt(&mut self, jlpt: u8) {
+        self.jlpt = Some(jlpt);
+    }
+    /// Rank among the 2500 most used kanji in newspapers, 1 being the most frequent.
+    pub fn frequency(&self) -> Option<u16> {
+        self.frequency
+    }
+    pub fn set_frequency(&mut self, frequency: u16) {
+        self.frequency = Some(frequency);
+    }
+    /// Number of the classical Kangxi radical, from 1 to 214.
+    pub fn radical(&self) -> Option<u8> {
+        self.radical
+    }
+    pub fn set_radical(&mut self, radical: u8) {
+        self.radical = Some(radical);
+    }
+}
+
+/// Parsed KANJIDIC2 dictionary kept in memory, indexed by character.
+#[derive(Debug, Default, Clone)]
+pub struct Kanjidic {
+    characters: HashMap<char, KanjiCharacter>,
+}
+
+impl Kanjidic {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn from_characters<I: IntoIterator<Item = KanjiCharacter>>(characters: I) -> Self {
+        let mut kanjidic = Self::new();
+        for character in characters {
+            kanjidic.add_character(character);
+        }
+        kanjidic
+    }
+    /// Parse a gzip-compressed KANJIDIC2 file into memory.
+    #[cfg(feature = "reader")]
+    pub fn from_kanjidic_gz<R: std::io::Read>(reader: R) -> Result<Self, crate::Error> {
+        use flate2::read::GzDecoder;
+        use std::io::BufReader;
+        Self::from_kanjidic(BufReader::new(GzDecoder::new(reader)))
+    }
+    /// Parse a KANJIDIC2 file into memory, plain or gzip, xz or zstd compressed.
+    #[cfg(feature = "reader")]
+    pub fn from_kanjidic_any<R: std::io::Read>(reader: R) -> Result<Self, crate::Error> {
+        Self::from_kanjidic(super::compression::decompress(reader)?)
+    }
+    /// Parse an uncompressed KANJIDIC2 file into memory.
+    #[cfg(feature = "reader")]
+    pub fn from_kanjidic<R: std::io::BufRead>(buf_reader: R) -> Result<Self, crate::Error> {
+        let mut kanjidic = Self::new();
+        for character_result in super::reader::KanjidicReader::new(buf_reader) {
+            kanjidic.add_character(character_result?);
+        }
+        Ok(kanjidic)
+    }
+    pub fn add_character(&mut self, character: KanjiCharacter) {
+        self.characters.insert(character.literal(), character);
+    }
+    pub fn len(&self) -> usize {
+        self.characters.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.characters.is_empty()
+    }
+    pub fn get(&self, literal: char) -> Option<&KanjiCharacter> {
+        self.characters.get(&literal)
+    }
+    /// Details of every kanji of a word, in order, skipping kana and unknown characters.
+    pub fn characters_of<'s>(&'s self, word: &'s str) -> impl Iterator<Item = &'s KanjiCharacter> {
+        word.chars().filter_map(move |c| self.get(c))
+    }
+}
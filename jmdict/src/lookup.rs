@@ -0,0 +1,102 @@
+use super::deinflect::Inflection;
+use super::entry::JMDictEntry;
+use super::entry_id::JMDictEntryId;
+use super::furigana::{self, FuriganaSegment};
+use super::kanji::Kanjidic;
+use super::name::NameEntry;
+use serde::{Deserialize, Serialize};
+
+/// Result of looking up a word identifier: every dictionary entry it designates.
+///
+/// When the word is a conjugated form, the candidates are the entries of its dictionary form,
+/// and the conjugations recognized are kept. Proper names with the same spelling, from the JMnedict
+/// dictionary, and the furigana of each candidate can be attached to the lookup.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WordLookup {
+    entry_id: JMDictEntryId<'static>,
+    candidates: Vec<JMDictEntry>,
+    #[serde(default)]
+    inflections: Vec<Inflection>,
+    #[serde(default)]
+    names: Vec<NameEntry>,
+    #[serde(default)]
+    furigana: Vec<Option<Vec<FuriganaSegment>>>,
+}
+
+impl WordLookup {
+    pub fn new(entry_id: JMDictEntryId<'static>, candidates: Vec<JMDictEntry>) -> Self {
+        Self {
+            entry_id,
+            candidates,
+            inflections: Vec::new(),
+            names: Vec::new(),
+            furigana: Vec::new(),
+        }
+    }
+    /// Lookup of a conjugated word, whose candidates are dictionary forms.
+    pub fn new_inflected(
+        entry_id: JMDictEntryId<'static>,
+        candidates: Vec<JMDictEntry>,
+        inflections: Vec<Inflection>,
+    ) -> Self {
+        Self {
+            entry_id,
+            candidates,
+            inflections,
+            names: Vec::new(),
+            furigana: Vec::new(),
+        }
+    }
+    /// Identifier that was looked up.
+    pub fn entry_id(&self) -> &JMDictEntryId<'static> {
+        &self.entry_id
+    }
+    /// Matching entries, most common first.
+    pub fn candidates(&self) -> &[JMDictEntry] {
+        &self.candidates
+    }
+    pub fn into_candidates(self) -> Vec<JMDictEntry> {
+        self.candidates
+    }
+    /// Conjugations recognized in the word, from the innermost to the outermost, if it was not
+    /// in dictionary form.
+    pub fn inflections(&self) -> &[Inflection] {
+        &self.inflections
+    }
+    pub fn is_inflected(&self) -> bool {
+        !self.inflections.is_empty()
+    }
+    /// Proper names matching the identifier.
+    pub fn names(&self) -> &[NameEntry] {
+        &self.names
+    }
+    pub fn set_names(&mut self, names: Vec<NameEntry>) {
+        self.names = names;
+    }
+    /// Furigana of the main spelling of a candidate, by its index in `candidates`, if aligned.
+    pub fn furigana(&self, candidate_index: usize) -> Option<&[FuriganaSegment]> {
+        self.furigana
+            .get(candidate_index)
+            .and_then(|segments| segments.as_ref())
+            .map(|segments| segments as &[FuriganaSegment])
+    }
+    /// Align the main reading of each candidate to the kanji of its main spelling, one kanji at a
+    /// time when their KANJIDIC2 readings allow it.
+    ///
+    /// Kana-only candidates and spellings that cannot be aligned have no furigana.
+    pub fn align_furigana(&mut self, kanjidic: &Kanjidic) {
+        self.furigana = self
+            .candidates
+            .iter()
+            .map(|entry| {
+                let kanji = entry.main_kanji()?;
+                let reading = entry.main_reading()?;
+                furigana::align_characters(kanji.string(), reading.string(), kanjidic)
+            })
+            .collect();
+    }
+    /// Whether several homographs match the identifier, so that the user has to pick one.
+    pub fn is_ambiguous(&self) -> bool {
+        self.candidates.len() > 1
+    }
+}
@@ -0,0 +1,216 @@
+//! Proper names from the JMnedict dictionary, such as people, places and companies.
+
+use super::entry::{CrossReference, Gloss, Kanji, Reading};
+use super::entry_id::JMDictEntryId;
+use super::kana;
+use super::tags::{Entities, NameType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Entry of the JMnedict dictionary of proper names.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NameEntry {
+    sequence: u32,
+    kanji_list: Vec<Kanji>,
+    reading_list: Vec<Reading>,
+    translation_list: Vec<Translation>,
+}
+
+impl NameEntry {
+    pub fn new(
+        sequence: u32,
+        kanji_list: Vec<Kanji>,
+        reading_list: Vec<Reading>,
+        translation_list: Vec<Translation>,
+    ) -> Self {
+        Self {
+            sequence,
+            kanji_list,
+            reading_list,
+            translation_list,
+        }
+    }
+    /// JMnedict `ent_seq` sequence number.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+    pub fn kanji(&self) -> &[Kanji] {
+        &self.kanji_list
+    }
+    pub fn readings(&self) -> &[Reading] {
+        &self.reading_list
+    }
+    pub fn translations(&self) -> &[Translation] {
+        &self.translation_list
+    }
+    /// Main spelling of the name: its first kanji, if any.
+    pub fn main_kanji(&self) -> Option<&Kanji> {
+        self.kanji_list.first()
+    }
+    /// Main reading of the name: its first reading applying to the main kanji.
+    pub fn main_reading(&self) -> Option<&Reading> {
+        self.main_kanji()
+            .and_then(|k| {
+                self.reading_list
+                    .iter()
+                    .find(|r| r.applies_to_kanji(k.string()))
+            })
+            .or_else(|| self.reading_list.first())
+    }
+    /// Kinds of name of all the translations, without duplicates.
+    pub fn name_types(&self) -> Vec<&NameType> {
+        let mut name_types: Vec<&NameType> = Vec::new();
+        for name_type in self.translation_list.iter().flat_map(|t| t.name_types()) {
+            if !name_types.contains(&name_type) {
+                name_types.push(name_type);
+            }
+        }
+        name_types
+    }
+}
+
+/// Translation of a name, from `<trans>`.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Translation {
+    name_types: Vec<NameType>,
+    cross_references: Vec<CrossReference>,
+    details: Vec<Gloss>,
+}
+
+impl Translation {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Kinds of name from `<name_type>`, such as surname or place.
+    pub fn name_types(&self) -> &[NameType] {
+        &self.name_types
+    }
+    pub fn add_name_type(&mut self, name_type: NameType) {
+        self.name_types.push(name_type);
+    }
+    /// Related entries from `<xref>`.
+    pub fn cross_references(&self) -> &[CrossReference] {
+        &self.cross_references
+    }
+    pub fn add_cross_reference(&mut self, cross_reference: CrossReference) {
+        self.cross_references.push(cross_reference);
+    }
+    /// Transcriptions or translations of the name from `<trans_det>`, such as `Koizumi`.
+    pub fn details(&self) -> &[Gloss] {
+        &self.details
+    }
+    pub fn add_detail(&mut self, detail: Gloss) {
+        self.details.push(detail);
+    }
+}
+
+/// Parsed JMnedict dictionary kept in memory, indexed by kanji, reading and sequence number.
+#[derive(Debug, Default, Clone)]
+pub struct JMnedict {
+    entities: Entities,
+    entries: Vec<NameEntry>,
+    kanji_index: HashMap<String, Vec<usize>>,
+    reading_index: HashMap<String, Vec<usize>>,
+    sequence_index: HashMap<u32, usize>,
+}
+
+impl JMnedict {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn from_entries<I: IntoIterator<Item = NameEntry>>(entries: I) -> Self {
+        let mut jmnedict = Self::new();
+        for entry in entries {
+            jmnedict.add_entry(entry);
+        }
+        jmnedict
+    }
+    /// Parse a gzip-compressed JMnedict file into memory.
+    #[cfg(feature = "reader")]
+    pub fn from_jmnedict_gz<R: std::io::Read>(reader: R) -> Result<Self, crate::Error> {
+        use flate2::read::GzDecoder;
+        use std::io::BufReader;
+        Self::from_jmnedict(BufReader::new(GzDecoder::new(reader)))
+    }
+    /// Parse a JMnedict file into memory, plain or gzip, xz or zstd compressed.
+    #[cfg(feature = "reader")]
+    pub fn from_jmnedict_any<R: std::io::Read>(reader: R) -> Result<Self, crate::Error> {
+        Self::from_jmnedict(super::compression::decompress(reader)?)
+    }
+    /// Parse an uncompressed JMnedict file into memory.
+    #[cfg(feature = "reader")]
+    pub fn from_jmnedict<R: std::io::BufRead>(buf_reader: R) -> Result<Self, crate::Error> {
+        let mut jmnedict = Self::new();
+        let mut jmnedict_reader = super::reader::JMnedictReader::new(buf_reader);
+        for entry_result in &mut jmnedict_reader {
+            jmnedict.add_entry(entry_result?);
+        }
+        jmnedict.set_entities(jmnedict_reader.into_entities());
+        Ok(jmnedict)
+    }
+    /// Entity definitions of the dictionary, describing the name types of its entries.
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+    pub fn set_entities(&mut self, entities: Entities) {
+        self.entities = entities;
+    }
+    pub fn add_entry(&mut self, entry: NameEntry) {
+        let index = self.entries.len();
+        for kanji in entry.kanji() {
+            self.kanji_index
+                .entry(kana::normalize(kanji.string()))
+                .or_default()
+                .push(index);
+        }
+        for reading in entry.readings() {
+            self.reading_index
+                .entry(kana::normalize(reading.string()))
+                .or_default()
+                .push(index);
+        }
+        self.sequence_index.insert(entry.sequence(), index);
+        self.entries.push(entry);
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn entries(&self) -> &[NameEntry] {
+        &self.entries
+    }
+    /// Names having the given kanji spelling, in dictionary order.
+    pub fn by_kanji<'s>(&'s self, kanji: &str) -> impl Iterator<Item = &'s NameEntry> {
+        self.indexed_entries(self.kanji_index.get(&kana::normalize(kanji)))
+    }
+    /// Names having the given kana reading, in dictionary order.
+    ///
+    /// The reading matches regardless of kana script and width, and may be given in romaji.
+    pub fn by_reading<'s>(&'s self, reading: &str) -> impl Iterator<Item = &'s NameEntry> {
+        self.indexed_entries(self.reading_index.get(&kana::normalize_word(reading)))
+    }
+    pub fn by_sequence(&self, sequence: u32) -> Option<&NameEntry> {
+        self.sequence_index
+            .get(&sequence)
+            .map(|&index| &self.entries[index])
+    }
+    /// All the names matching an entry identifier, in dictionary order.
+    pub fn lookup(&self, entry_id: &JMDictEntryId) -> Vec<&NameEntry> {
+        match entry_id {
+            JMDictEntryId::Kanji(kanji_string) => self.by_kanji(kanji_string).collect(),
+            JMDictEntryId::Sequence(sequence) => self.by_sequence(*sequence).into_iter().collect(),
+            JMDictEntryId::Reading(reading_string) => self.by_reading(reading_string).collect(),
+        }
+    }
+    fn indexed_entries<'s>(
+        &'s self,
+        indices: Option<&'s Vec<usize>>,
+    ) -> impl Iterator<Item = &'s NameEntry> {
+        indices
+            .into_iter()
+            .flatten()
+            .map(move |&index| &self.entries[index])
+    }
+}
@@ -0,0 +1,857 @@
+use super::entry::{self, JMDictEntry};
+use super::kanji::KanjiCharacter;
+use super::name::{NameEntry, Translation};
+use super::tags::{self, Entities};
+use super::Error;
+use quick_xml::events::Event;
+use std::io::prelude::*;
+
+const ELEM_JMDICT: &[u8] = b"JMdict";
+const ELEM_ENTRY: &[u8] = b"entry";
+const ELEM_SEQUENCE: &[u8] = b"ent_seq";
+const ELEM_KANJI: &[u8] = b"k_ele";
+const ELEM_READING: &[u8] = b"r_ele";
+const ELEM_SENSE: &[u8] = b"sense";
+const ELEM_JMNEDICT: &[u8] = b"JMnedict";
+const ELEM_TRANSLATION: &[u8] = b"trans";
+const ELEM_KANJIDIC: &[u8] = b"kanjidic2";
+const ELEM_CHARACTER: &[u8] = b"character";
+
+/// Streaming reader over the entries of a JMdict file.
+///
+/// Entries are parsed one at a time as the iterator advances. Iteration stops after the first
+/// error.
+pub struct JMDictReader<R: BufRead> {
+    xml_reader: quick_xml::Reader<R>,
+    xml_buf: Vec<u8>,
+    entities: Entities,
+    languages: Vec<String>,
+    finished: bool,
+}
+
+impl<R: Read> JMDictReader<std::io::BufReader<flate2::read::GzDecoder<R>>> {
+    /// Read a gzip-compressed JMdict file.
+    pub fn from_gz(reader: R) -> Self {
+        use flate2::read::GzDecoder;
+        use std::io::BufReader;
+        Self::new(BufReader::new(GzDecoder::new(reader)))
+    }
+}
+
+impl<'r> JMDictReader<Box<dyn BufRead + 'r>> {
+    /// Read a JMdict file, plain or compressed, detecting its format from its first bytes.
+    pub fn from_any<R: Read + 'r>(reader: R) -> Result<Self, Error> {
+        Ok(Self::new(super::compression::decompress(reader)?))
+    }
+}
+
+impl<R: BufRead> JMDictReader<R> {
+    /// Read an uncompressed JMdict file.
+    pub fn new(buf_reader: R) -> Self {
+        JMDictReader {
+            xml_reader: quick_xml::Reader::from_reader(buf_reader),
+            xml_buf: Vec::new(),
+            entities: Entities::new(),
+            languages: Vec::new(),
+            finished: false,
+        }
+    }
+    /// Only read the glosses in the given ISO 639-2 languages, falling back to English for
+    /// entries without any, see `JMDictEntry::select_languages`. All the glosses are read by
+    /// default.
+    pub fn set_languages(&mut self, languages: Vec<String>) {
+        self.languages = languages;
+    }
+    /// Entity definitions of the file DOCTYPE, available once the first entry has been read.
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+    pub fn into_entities(self) -> Entities {
+        self.entities
+    }
+    /// Read up to the next entry, or `None` at the end of the file.
+    fn read_next_entry(&mut self) -> Result<Option<JMDictEntry>, Error> {
+        read_next(
+            &mut self.xml_reader,
+            &mut self.xml_buf,
+            Some(&mut self.entities),
+            ELEM_JMDICT,
+            ELEM_ENTRY,
+            read_entry,
+        )
+    }
+}
+
+impl<R: BufRead> Iterator for JMDictReader<R> {
+    type Item = Result<JMDictEntry, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = self.read_next_entry();
+        match result {
+            Ok(Some(mut entry)) => {
+                entry.select_languages(&self.languages);
+                Some(Ok(entry))
+            }
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Streaming reader over the entries of a JMnedict file of proper names.
+///
+/// Entries are parsed one at a time as the iterator advances. Iteration stops after the first
+/// error.
+pub struct JMnedictReader<R: BufRead> {
+    xml_reader: quick_xml::Reader<R>,
+    xml_buf: Vec<u8>,
+    entities: Entities,
+    finished: bool,
+}
+
+impl<R: Read> JMnedictReader<std::io::BufReader<flate2::read::GzDecoder<R>>> {
+    /// Read a gzip-compressed JMnedict file.
+    pub fn from_gz(reader: R) -> Self {
+        use flate2::read::GzDecoder;
+        use std::io::BufReader;
+        Self::new(BufReader::new(GzDecoder::new(reader)))
+    }
+}
+
+impl<'r> JMnedictReader<Box<dyn BufRead + 'r>> {
+    /// Read a JMnedict file, plain or compressed, detecting its format from its first bytes.
+    pub fn from_any<R: Read + 'r>(reader: R) -> Result<Self, Error> {
+        Ok(Self::new(super::compression::decompress(reader)?))
+    }
+}
+
+impl<R: BufRead> JMnedictReader<R> {
+    /// Read an uncompressed JMnedict file.
+    pub fn new(buf_reader: R) -> Self {
+        JMnedictReader {
+            xml_reader: quick_xml::Reader::from_reader(buf_reader),
+            xml_buf: Vec::new(),
+            entities: Entities::new(),
+            finished: false,
+        }
+    }
+    /// Entity definitions of the file DOCTYPE, available once the first entry has been read.
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+    pub fn into_entities(self) -> Entities {
+        self.entities
+    }
+}
+
+impl<R: BufRead> Iterator for JMnedictReader<R> {
+    type Item = Result<NameEntry, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = read_next(
+            &mut self.xml_reader,
+            &mut self.xml_buf,
+            Some(&mut self.entities),
+            ELEM_JMNEDICT,
+            ELEM_ENTRY,
+            read_name_entry,
+        );
+        match result {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Streaming reader over the characters of a KANJIDIC2 file.
+///
+/// Characters are parsed one at a time as the iterator advances. Iteration stops after the first
+/// error.
+pub struct KanjidicReader<R: BufRead> {
+    xml_reader: quick_xml::Reader<R>,
+    xml_buf: Vec<u8>,
+    finished: bool,
+}
+
+impl<R: Read> KanjidicReader<std::io::BufReader<flate2::read::GzDecoder<R>>> {
+    /// Read a gzip-compressed KANJIDIC2 file.
+    pub fn from_gz(reader: R) -> Self {
+        use flate2::read::GzDecoder;
+        use std::io::BufReader;
+        Self::new(BufReader::new(GzDecoder::new(reader)))
+    }
+}
+
+impl<'r> KanjidicReader<Box<dyn BufRead + 'r>> {
+    /// Read a KANJIDIC2 file, plain or compressed, detecting its format from its first bytes.
+    pub fn from_any<R: Read + 'r>(reader: R) -> Result<Self, Error> {
+        Ok(Self::new(super::compression::decompress(reader)?))
+    }
+}
+
+impl<R: BufRead> KanjidicReader<R> {
+    /// Read an uncompressed KANJIDIC2 file.
+    pub fn new(buf_reader: R) -> Self {
+        KanjidicReader {
+            xml_reader: quick_xml::Reader::from_reader(buf_reader),
+            xml_buf: Vec::new(),
+            finished: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for KanjidicReader<R> {
+    type Item = Result<KanjiCharacter, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = read_next(
+            &mut self.xml_reader,
+            &mut self.xml_buf,
+            None,
+            ELEM_KANJIDIC,
+            ELEM_CHARACTER,
+            read_character,
+        );
+        match result {
+            Ok(Some(character)) => Some(Ok(character)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Read up to the next entry element of a dictionary file, or `None` at the end of its root
+/// element.
+///
+/// The entity definitions are read from the DOCTYPE on the way, if needed. A file ending before
+/// the root element is closed, including an empty file or one without a root element, is
+/// reported as truncated.
+fn read_next<R: BufRead, T>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    xml_buf: &mut Vec<u8>,
+    mut entities: Option<&mut Entities>,
+    root: &[u8],
+    entry_element: &[u8],
+    read_entry: fn(&mut quick_xml::Reader<R>, &mut Vec<u8>) -> Result<T, Error>,
+) -> Result<Option<T>, Error> {
+    loop {
+        xml_buf.clear();
+        match xml_reader.read_event(xml_buf) {
+            Ok(Event::Start(ref e)) if e.name() == entry_element => {
+                return read_entry(xml_reader, xml_buf).map(Some);
+            }
+            Ok(Event::DocType(ref doctype_buffer)) => {
+                if let Some(entities) = entities.as_mut() {
+                    **entities = Entities::from_doctype(doctype_buffer);
+                }
+            }
+            Ok(Event::End(ref e)) if e.name() == root => return Ok(None),
+            Ok(Event::Eof) => {
+                return Err(Error::unexpected_eof(xml_reader.buffer_position(), root));
+            }
+            Err(e) => return Err(Error::xml(xml_reader.buffer_position(), root, e)),
+            _ => (),
+        }
+    }
+}
+
+/// Read the contents of an `<entry>` element, after its start tag.
+fn read_entry<R: BufRead>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    xml_buf: &mut Vec<u8>,
+) -> Result<JMDictEntry, Error> {
+    let mut entry_sequence: u32 = 0;
+    let mut entry_kanji: Vec<entry::Kanji> = Vec::new();
+    let mut entry_reading: Vec<entry::Reading> = Vec::new();
+    let mut entry_sense: Vec<entry::Sense> = Vec::new();
+    loop {
+        match xml_reader.read_event(xml_buf) {
+            Ok(Event::Start(ref e)) => match e.name() {
+                // entry sequence number
+                ELEM_SEQUENCE => entry_sequence = read_sequence(xml_reader, xml_buf)?,
+                // kanji element
+                ELEM_KANJI => read_kanji_element(xml_reader, xml_buf, &mut entry_kanji)?,
+                // reading element
+                ELEM_READING => read_reading_element(xml_reader, xml_buf, &mut entry_reading)?,
+                // sense element
+                ELEM_SENSE => entry_sense.push(read_sense_element(xml_reader, xml_buf)?),
+                _ => (),
+            },
+            Ok(Event::End(ref e)) if e.name() == ELEM_ENTRY => {
+                return Ok(JMDictEntry::new(
+                    entry_sequence,
+                    entry_kanji,
+                    entry_reading,
+                    entry_sense,
+                ));
+            }
+            Ok(Event::Eof) => {
+                return Err(Error::unexpected_eof(
+                    xml_reader.buffer_position(),
+                    ELEM_ENTRY,
+                ))
+            }
+            Err(e) => return Err(Error::xml(xml_reader.buffer_position(), ELEM_ENTRY, e)),
+            _ => (),
+        }
+    }
+}
+
+/// Read the contents of a JMnedict `<entry>` element, after its start tag.
+fn read_name_entry<R: BufRead>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    xml_buf: &mut Vec<u8>,
+) -> Result<NameEntry, Error> {
+    let mut entry_sequence: u32 = 0;
+    let mut entry_kanji: Vec<entry::Kanji> = Vec::new();
+    let mut entry_reading: Vec<entry::Reading> = Vec::new();
+    let mut entry_translation: Vec<Translation> = Vec::new();
+    loop {
+        match xml_reader.read_event(xml_buf) {
+            Ok(Event::Start(ref e)) => match e.name() {
+                ELEM_SEQUENCE => entry_sequence = read_sequence(xml_reader, xml_buf)?,
+                ELEM_KANJI => read_kanji_element(xml_reader, xml_buf, &mut entry_kanji)?,
+                ELEM_READING => read_reading_element(xml_reader, xml_buf, &mut entry_reading)?,
+                ELEM_TRANSLATION => {
+                    entry_translation.push(read_translation_element(xml_reader, xml_buf)?)
+                }
+                _ => (),
+            },
+            Ok(Event::End(ref e)) if e.name() == ELEM_ENTRY => {
+                return Ok(NameEntry::new(
+                    entry_sequence,
+                    entry_kanji,
+                    entry_reading,
+                    entry_translation,
+                ));
+            }
+            Ok(Event::Eof) => {
+                return Err(Error::unexpected_eof(
+                    xml_reader.buffer_position(),
+                    ELEM_ENTRY,
+                ))
+            }
+            Err(e) => return Err(Error::xml(xml_reader.buffer_position(), ELEM_ENTRY, e)),
+            _ => (),
+        }
+    }
+}
+
+/// Read the contents of a KANJIDIC2 `<character>` element, after its start tag.
+///
+/// Only the readings and meanings, the classical radical and the `<misc>` information are kept.
+fn read_character<R: BufRead>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    xml_buf: &mut Vec<u8>,
+) -> Result<KanjiCharacter, Error> {
+    const ELEM_LITERAL: &[u8] = b"literal";
+    const ELEM_RAD_VALUE: &[u8] = b"rad_value";
+    const ELEM_GRADE: &[u8] = b"grade";
+    const ELEM_STROKE_COUNT: &[u8] = b"stroke_count";
+    const ELEM_FREQ: &[u8] = b"freq";
+    const ELEM_JLPT: &[u8] = b"jlpt";
+    const ELEM_READING: &[u8] = b"reading";
+    const ELEM_MEANING: &[u8] = b"meaning";
+    const ELEM_NANORI: &[u8] = b"nanori";
+    const ATTR_RAD_TYPE: &[u8] = b"rad_type";
+    const ATTR_R_TYPE: &[u8] = b"r_type";
+    const ATTR_M_LANG: &[u8] = b"m_lang";
+    let mut character: Option<KanjiCharacter> = None;
+    loop {
+        match xml_reader.read_event(xml_buf) {
+            Ok(Event::Start(ref e)) => match e.name() {
+                ELEM_LITERAL => {
+                    let literal_text = read_element_text(xml_reader, ELEM_LITERAL, xml_buf)?;
+                    let literal = literal_text.chars().next().ok_or_else(|| {
+                        Error::invalid_data(
+                            xml_reader.buffer_position(),
+                            ELEM_LITERAL,
+                            "empty literal",
+                        )
+                    })?;
+                    character = Some(KanjiCharacter::new(literal));
+                }
+                ELEM_RAD_VALUE => {
+                    let is_classical =
+                        attribute_string(e, ATTR_RAD_TYPE).is_some_and(|t| t == "classical");
+                    let radical_text = read_element_text(xml_reader, ELEM_RAD_VALUE, xml_buf)?;
+                    if let (true, Some(character), Ok(radical)) = (
+                        is_classical,
+                        character.as_mut(),
+                        radical_text.trim().parse(),
+                    ) {
+                        character.set_radical(radical);
+                    }
+                }
+                ELEM_GRADE => {
+                    let grade_text = read_element_text(xml_reader, ELEM_GRADE, xml_buf)?;
+                    if let (Some(character), Ok(grade)) =
+                        (character.as_mut(), grade_text.trim().parse())
+                    {
+                        character.set_grade(grade);
+                    }
+                }
+                ELEM_STROKE_COUNT => {
+                    let stroke_text = read_element_text(xml_reader, ELEM_STROKE_COUNT, xml_buf)?;
+                    // the first stroke count is the accepted one, the others are common miscounts
+                    if let (Some(character), Ok(stroke_count)) =
+                        (character.as_mut(), stroke_text.trim().parse())
+                    {
+                        if character.stroke_count().is_none() {
+                            character.set_stroke_count(stroke_count);
+                        }
+                    }
+                }
+                ELEM_FREQ => {
+                    let frequency_text = read_element_text(xml_reader, ELEM_FREQ, xml_buf)?;
+                    if let (Some(character), Ok(frequency)) =
+                        (character.as_mut(), frequency_text.trim().parse())
+                    {
+                        character.set_frequency(frequency);
+                    }
+                }
+                ELEM_JLPT => {
+                    let jlpt_text = read_element_text(xml_reader, ELEM_JLPT, xml_buf)?;
+                    if let (Some(character), Ok(jlpt)) =
+                        (character.as_mut(), jlpt_text.trim().parse())
+                    {
+                        character.set_jlpt(jlpt);
+                    }
+                }
+                ELEM_READING => {
+                    let reading_type = attribute_string(e, ATTR_R_TYPE);
+                    let reading_text = read_element_text(xml_reader, ELEM_READING, xml_buf)?;
+                    if let Some(character) = character.as_mut() {
+                        match reading_type.as_ref().map(|t| t as &str) {
+                            Some("ja_on") => character.add_on_reading(reading_text),
+                            Some("ja_kun") => character.add_kun_reading(reading_text),
+                            _ => (),
+                        }
+                    }
+                }
+                ELEM_MEANING => {
+                    let lang_attribute_opt = attribute_string(e, ATTR_M_LANG);
+                    let meaning_text = read_element_text(xml_reader, ELEM_MEANING, xml_buf)?;
+                    let meaning = if let Some(lang_attribute) = lang_attribute_opt {
+                        entry::Gloss::new_with_lang(meaning_text, lang_attribute)
+                    } else {
+                        entry::Gloss::new(meaning_text)
+                    };
+                    if let Some(character) = character.as_mut() {
+                        character.add_meaning(meaning);
+                    }
+                }
+                ELEM_NANORI => {
+                    let nanori_text = read_element_text(xml_reader, ELEM_NANORI, xml_buf)?;
+                    if let Some(character) = character.as_mut() {
+                        character.add_nanori(nanori_text);
+                    }
+                }
+                _ => (),
+            },
+            Ok(Event::End(ref e)) if e.name() == ELEM_CHARACTER => {
+                return character.ok_or_else(|| {
+                    Error::invalid_data(
+                        xml_reader.buffer_position(),
+                        ELEM_CHARACTER,
+                        "character without literal",
+                    )
+                });
+            }
+            Ok(Event::Eof) => {
+                return Err(Error::unexpected_eof(
+                    xml_reader.buffer_position(),
+                    ELEM_CHARACTER,
+                ))
+            }
+            Err(e) => return Err(Error::xml(xml_reader.buffer_position(), ELEM_CHARACTER, e)),
+            _ => (),
+        }
+    }
+}
+
+/// Read the contents of an `<ent_seq>` element as a sequence number.
+fn read_sequence<R: BufRead>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    xml_buf: &mut Vec<u8>,
+) -> Result<u32, Error> {
+    let sequence_text = read_element_text(xml_reader, ELEM_SEQUENCE, xml_buf)?;
+    sequence_text.trim().parse().map_err(|_| {
+        Error::invalid_data(
+            xml_reader.buffer_position(),
+            ELEM_SEQUENCE,
+            format!("invalid sequence number {:?}", sequence_text),
+        )
+    })
+}
+
+/// Read the contents of a `<k_ele>` element into the kanji list of an entry.
+fn read_kanji_element<R: BufRead>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    xml_buf: &mut Vec<u8>,
+    entry_kanji: &mut Vec<entry::Kanji>,
+) -> Result<(), Error> {
+    const ELEM_KEB: &[u8] = b"keb";
+    const ELEM_KE_INF: &[u8] = b"ke_inf";
+    const ELEM_KE_PRI: &[u8] = b"ke_pri";
+    'kanji_inner: loop {
+        match xml_reader.read_event(xml_buf) {
+            Ok(Event::Start(ref e1)) => match e1.name() {
+                ELEM_KEB => {
+                    let kanji_text = read_element_text(xml_reader, ELEM_KEB, xml_buf)?;
+                    entry_kanji.push(entry::Kanji::new(kanji_text));
+                }
+                ELEM_KE_INF => {
+                    let info_opt = read_entity_name(xml_reader, ELEM_KE_INF, xml_buf)?;
+                    if let (Some(kanji), Some(info)) = (entry_kanji.last_mut(), info_opt) {
+                        kanji.add_info(tags::KanjiInfo::from_code(&info));
+                    }
+                }
+                ELEM_KE_PRI => {
+                    let priority_opt = entry::Priority::from_code(&read_element_text(
+                        xml_reader,
+                        ELEM_KE_PRI,
+                        xml_buf,
+                    )?);
+                    if let (Some(kanji), Some(priority)) = (entry_kanji.last_mut(), priority_opt) {
+                        kanji.add_priority(priority);
+                    }
+                }
+                _ => (),
+            },
+            Ok(Event::Eof) => {
+                return Err(Error::unexpected_eof(
+                    xml_reader.buffer_position(),
+                    ELEM_KANJI,
+                ))
+            }
+            Ok(Event::End(ref e1)) if e1.name() == ELEM_KANJI => break 'kanji_inner,
+            Err(e) => return Err(Error::xml(xml_reader.buffer_position(), ELEM_KANJI, e)),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Read the contents of a `<r_ele>` element into the reading list of an entry.
+fn read_reading_element<R: BufRead>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    xml_buf: &mut Vec<u8>,
+    entry_reading: &mut Vec<entry::Reading>,
+) -> Result<(), Error> {
+    const ELEM_REB: &[u8] = b"reb";
+    const ELEM_RE_NOKANJI: &[u8] = b"re_nokanji";
+    const ELEM_RE_RESTR: &[u8] = b"re_restr";
+    const ELEM_RE_INF: &[u8] = b"re_inf";
+    const ELEM_RE_PRI: &[u8] = b"re_pri";
+    'reading_inner: loop {
+        match xml_reader.read_event(xml_buf) {
+            Ok(Event::Start(ref e1)) => match e1.name() {
+                ELEM_REB => {
+                    let reading_text = read_element_text(xml_reader, ELEM_REB, xml_buf)?;
+                    entry_reading.push(entry::Reading::new(reading_text));
+                }
+                ELEM_RE_NOKANJI => {
+                    if let Some(reading) = entry_reading.last_mut() {
+                        reading.set_no_kanji(true);
+                    }
+                    read_to_element_end(xml_reader, ELEM_RE_NOKANJI, xml_buf)?;
+                }
+                ELEM_RE_RESTR => {
+                    let kanji_text = read_element_text(xml_reader, ELEM_RE_RESTR, xml_buf)?;
+                    if let Some(reading) = entry_reading.last_mut() {
+                        reading.add_restriction(kanji_text);
+                    }
+                }
+                ELEM_RE_INF => {
+                    let info_opt = read_entity_name(xml_reader, ELEM_RE_INF, xml_buf)?;
+                    if let (Some(reading), Some(info)) = (entry_reading.last_mut(), info_opt) {
+                        reading.add_info(tags::ReadingInfo::from_code(&info));
+                    }
+                }
+                ELEM_RE_PRI => {
+                    let priority_opt = entry::Priority::from_code(&read_element_text(
+                        xml_reader,
+                        ELEM_RE_PRI,
+                        xml_buf,
+                    )?);
+                    if let (Some(reading), Some(priority)) =
+                        (entry_reading.last_mut(), priority_opt)
+                    {
+                        reading.add_priority(priority);
+                    }
+                }
+                _ => (),
+            },
+            Ok(Event::Empty(ref e1)) if e1.name() == ELEM_RE_NOKANJI => {
+                if let Some(reading) = entry_reading.last_mut() {
+                    reading.set_no_kanji(true);
+                }
+            }
+            Ok(Event::Eof) => {
+                return Err(Error::unexpected_eof(
+                    xml_reader.buffer_position(),
+                    ELEM_READING,
+                ))
+            }
+            Ok(Event::End(ref e1)) if e1.name() == ELEM_READING => break 'reading_inner,
+            Err(e) => return Err(Error::xml(xml_reader.buffer_position(), ELEM_READING, e)),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Read the contents of a `<sense>` element.
+fn read_sense_element<R: BufRead>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    xml_buf: &mut Vec<u8>,
+) -> Result<entry::Sense, Error> {
+    const ELEM_STAGK: &[u8] = b"stagk";
+    const ELEM_STAGR: &[u8] = b"stagr";
+    const ELEM_POS: &[u8] = b"pos";
+    const ELEM_XREF: &[u8] = b"xref";
+    const ELEM_ANT: &[u8] = b"ant";
+    const ELEM_FIELD: &[u8] = b"field";
+    const ELEM_MISC: &[u8] = b"misc";
+    const ELEM_S_INF: &[u8] = b"s_inf";
+    const ELEM_LSOURCE: &[u8] = b"lsource";
+    const ELEM_DIAL: &[u8] = b"dial";
+    const ELEM_GLOSS: &[u8] = b"gloss";
+    const ATTR_GLOSS_LANG: &[u8] = b"xml:lang";
+    const ATTR_GLOSS_TYPE: &[u8] = b"g_type";
+    const ATTR_GLOSS_GENDER: &[u8] = b"g_gend";
+    let mut current_sense = entry::Sense::new();
+    'sense_inner: loop {
+        match xml_reader.read_event(xml_buf) {
+            Ok(Event::Start(ref e1)) => match e1.name() {
+                ELEM_STAGK => {
+                    let kanji_text = read_element_text(xml_reader, ELEM_STAGK, xml_buf)?;
+                    current_sense.add_kanji_restriction(kanji_text);
+                }
+                ELEM_STAGR => {
+                    let reading_text = read_element_text(xml_reader, ELEM_STAGR, xml_buf)?;
+                    current_sense.add_reading_restriction(reading_text);
+                }
+                ELEM_POS => {
+                    if let Some(pos) = read_entity_name(xml_reader, ELEM_POS, xml_buf)? {
+                        current_sense.add_part_of_speech(tags::PartOfSpeech::from_code(&pos));
+                    }
+                }
+                ELEM_XREF => {
+                    let xref_text = read_element_text(xml_reader, ELEM_XREF, xml_buf)?;
+                    current_sense.add_cross_reference(entry::CrossReference::parse(&xref_text));
+                }
+                ELEM_ANT => {
+                    let ant_text = read_element_text(xml_reader, ELEM_ANT, xml_buf)?;
+                    current_sense.add_antonym(entry::CrossReference::parse(&ant_text));
+                }
+                ELEM_FIELD => {
+                    if let Some(field) = read_entity_name(xml_reader, ELEM_FIELD, xml_buf)? {
+                        current_sense.add_field(tags::Field::from_code(&field));
+                    }
+                }
+                ELEM_MISC => {
+                    if let Some(misc) = read_entity_name(xml_reader, ELEM_MISC, xml_buf)? {
+                        current_sense.add_misc(tags::Misc::from_code(&misc));
+                    }
+                }
+                ELEM_S_INF => {
+                    let info_text = read_element_text(xml_reader, ELEM_S_INF, xml_buf)?;
+                    current_sense.add_info(info_text);
+                }
+                ELEM_LSOURCE => {
+                    let mut language_source = read_language_source_attributes(e1);
+                    let source_text = read_element_text(xml_reader, ELEM_LSOURCE, xml_buf)?;
+                    language_source.set_text(source_text);
+                    current_sense.add_language_source(language_source);
+                }
+                ELEM_DIAL => {
+                    if let Some(dialect) = read_entity_name(xml_reader, ELEM_DIAL, xml_buf)? {
+                        current_sense.add_dialect(tags::Dialect::from_code(&dialect));
+                    }
+                }
+                ELEM_GLOSS => {
+                    let lang_attribute_opt = attribute_string(e1, ATTR_GLOSS_LANG);
+                    let type_attribute_opt = attribute_string(e1, ATTR_GLOSS_TYPE);
+                    let gender_attribute_opt = attribute_string(e1, ATTR_GLOSS_GENDER);
+                    let gloss_text = read_element_text(xml_reader, ELEM_GLOSS, xml_buf)?;
+                    let mut gloss = if let Some(lang_attribute) = lang_attribute_opt {
+                        entry::Gloss::new_with_lang(gloss_text, lang_attribute)
+                    } else {
+                        entry::Gloss::new(gloss_text)
+                    };
+                    if let Some(type_attribute) = type_attribute_opt {
+                        gloss.set_gloss_type(tags::GlossType::from_code(&type_attribute));
+                    }
+                    if let Some(gender_attribute) = gender_attribute_opt {
+                        gloss.set_gender(gender_attribute);
+                    }
+                    current_sense.add_gloss(gloss);
+                }
+                _ => (),
+            },
+            Ok(Event::Empty(ref e1)) if e1.name() == ELEM_LSOURCE => {
+                current_sense.add_language_source(read_language_source_attributes(e1));
+            }
+            Ok(Event::End(ref e1)) if e1.name() == ELEM_SENSE => break 'sense_inner,
+            Ok(Event::Eof) => {
+                return Err(Error::unexpected_eof(
+                    xml_reader.buffer_position(),
+                    ELEM_SENSE,
+                ))
+            }
+            Err(e) => return Err(Error::xml(xml_reader.buffer_position(), ELEM_SENSE, e)),
+            _ => (),
+        }
+    }
+    Ok(current_sense)
+}
+
+/// Read the contents of a JMnedict `<trans>` element.
+fn read_translation_element<R: BufRead>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    xml_buf: &mut Vec<u8>,
+) -> Result<Translation, Error> {
+    const ELEM_NAME_TYPE: &[u8] = b"name_type";
+    const ELEM_XREF: &[u8] = b"xref";
+    const ELEM_TRANS_DET: &[u8] = b"trans_det";
+    const ATTR_TRANS_DET_LANG: &[u8] = b"xml:lang";
+    let mut current_translation = Translation::new();
+    'translation_inner: loop {
+        match xml_reader.read_event(xml_buf) {
+            Ok(Event::Start(ref e1)) => match e1.name() {
+                ELEM_NAME_TYPE => {
+                    if let Some(name_type) = read_entity_name(xml_reader, ELEM_NAME_TYPE, xml_buf)?
+                    {
+                        current_translation.add_name_type(tags::NameType::from_code(&name_type));
+                    }
+                }
+                ELEM_XREF => {
+                    let xref_text = read_element_text(xml_reader, ELEM_XREF, xml_buf)?;
+                    current_translation
+                        .add_cross_reference(entry::CrossReference::parse(&xref_text));
+                }
+                ELEM_TRANS_DET => {
+                    let lang_attribute_opt = attribute_string(e1, ATTR_TRANS_DET_LANG);
+                    let detail_text = read_element_text(xml_reader, ELEM_TRANS_DET, xml_buf)?;
+                    let detail = if let Some(lang_attribute) = lang_attribute_opt {
+                        entry::Gloss::new_with_lang(detail_text, lang_attribute)
+                    } else {
+                        entry::Gloss::new(detail_text)
+                    };
+                    current_translation.add_detail(detail);
+                }
+                _ => (),
+            },
+            Ok(Event::End(ref e1)) if e1.name() == ELEM_TRANSLATION => break 'translation_inner,
+            Ok(Event::Eof) => {
+                return Err(Error::unexpected_eof(
+                    xml_reader.buffer_position(),
+                    ELEM_TRANSLATION,
+                ))
+            }
+            Err(e) => {
+                return Err(Error::xml(
+                    xml_reader.buffer_position(),
+                    ELEM_TRANSLATION,
+                    e,
+                ))
+            }
+            _ => (),
+        }
+    }
+    Ok(current_translation)
+}
+
+/// Value of an element attribute, if present and valid UTF-8.
+fn attribute_string(element: &quick_xml::events::BytesStart, key: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .filter_map(Result::ok)
+        .filter(|a| a.key == key)
+        .filter_map(|a| std::str::from_utf8(&a.value).map(String::from).ok())
+        .next()
+}
+
+/// Language source without its text, from the attributes of an `<lsource>` element.
+fn read_language_source_attributes(
+    element: &quick_xml::events::BytesStart,
+) -> entry::LanguageSource {
+    const ATTR_LANG: &[u8] = b"xml:lang";
+    const ATTR_LS_TYPE: &[u8] = b"ls_type";
+    const ATTR_LS_WASEI: &[u8] = b"ls_wasei";
+    let is_part = attribute_string(element, ATTR_LS_TYPE).is_some_and(|t| t == "part");
+    let is_wasei = attribute_string(element, ATTR_LS_WASEI).is_some_and(|w| w == "y");
+    entry::LanguageSource::new(attribute_string(element, ATTR_LANG), is_part, is_wasei)
+}
+
+/// Read the text contents of an element, up to its end tag.
+fn read_element_text<R: BufRead>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    end: &[u8],
+    xml_buf: &mut Vec<u8>,
+) -> Result<String, Error> {
+    xml_reader
+        .read_text(end, xml_buf)
+        .map_err(|e| Error::xml(xml_reader.buffer_position(), end, e))
+}
+
+/// Skip the contents of an element, up to its end tag.
+fn read_to_element_end<R: BufRead>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    end: &[u8],
+    xml_buf: &mut Vec<u8>,
+) -> Result<(), Error> {
+    xml_reader
+        .read_to_end(end, xml_buf)
+        .map_err(|e| Error::xml(xml_reader.buffer_position(), end, e))
+}
+
+/// Read the contents of an element holding a single entity reference, such as `<ke_inf>&iK;</ke_inf>`.
+///
+/// Returns the entity name without the surrounding `&` and `;`, or `None` if the element is empty.
+fn read_entity_name<R: BufRead>(
+    xml_reader: &mut quick_xml::Reader<R>,
+    end: &[u8],
+    xml_buf: &mut Vec<u8>,
+) -> Result<Option<String>, Error> {
+    let entity_name = match xml_reader.read_event(xml_buf) {
+        Ok(Event::Text(ref e)) => std::str::from_utf8(e.escaped()).ok().map(|text| {
+            let text = text.trim();
+            let text = text.trim_start_matches('&').trim_end_matches(';');
+            String::from(text)
+        }),
+        Ok(Event::End(_)) => return Ok(None),
+        Ok(Event::Eof) => return Err(Error::unexpected_eof(xml_reader.buffer_position(), end)),
+        Ok(_) => None,
+        Err(e) => return Err(Error::xml(xml_reader.buffer_position(), end, e)),
+    };
+    read_to_element_end(xml_reader, end, xml_buf)?;
+    Ok(entity_name)
+}
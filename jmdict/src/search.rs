@@ -0,0 +1,667 @@
+use super::entry::{Gloss, JMDictEntry, ENGLISH};
+use super::kana;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+
+/// Location of a gloss in a dictionary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GlossPosting {
+    entry_index: usize,
+    sense_index: usize,
+    gloss_index: usize,
+}
+
+/// Inverted index from the words of English glosses to the entries they translate.
+#[derive(Debug, Default, Clone)]
+pub struct GlossIndex {
+    postings: HashMap<String, Vec<GlossPosting>>,
+}
+
+/// Entry matching a gloss search, with the first of its glosses containing every query word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlossMatch {
+    entry_index: usize,
+    sense_index: usize,
+    gloss_index: usize,
+    exact: bool,
+}
+
+impl GlossMatch {
+    /// Index of the matching entry in the dictionary.
+    pub fn entry_index(&self) -> usize {
+        self.entry_index
+    }
+    pub fn sense_index(&self) -> usize {
+        self.sense_index
+    }
+    pub fn gloss_index(&self) -> usize {
+        self.gloss_index
+    }
+    /// Whether the gloss is the query itself, ignoring case, punctuation, parenthesized notes
+    /// and the leading "to" of verbs.
+    pub fn exact(&self) -> bool {
+        self.exact
+    }
+}
+
+/// Lowercase alphanumeric words of a text.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Words of a gloss, without its parenthesized notes such as "(something)".
+fn gloss_head_words(text: &str) -> Vec<String> {
+    let mut head = String::with_capacity(text.len());
+    let mut depth = 0usize;
+    for c in text.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if depth == 0 => head.push(c),
+            _ => (),
+        }
+    }
+    tokenize(&head).collect()
+}
+
+/// Whether a gloss is searchable in English.
+fn is_english(gloss: &Gloss) -> bool {
+    gloss.language() == ENGLISH
+}
+
+impl GlossIndex {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Index the English glosses of an entry, stored at `entry_index` in the dictionary.
+    pub fn add_entry(&mut self, entry_index: usize, entry: &JMDictEntry) {
+        for (sense_index, sense) in entry.senses().iter().enumerate() {
+            for (gloss_index, gloss) in sense.glosses().iter().enumerate() {
+                if !is_english(gloss) {
+                    continue;
+                }
+                let mut words: Vec<String> = tokenize(gloss.text()).collect();
+                words.sort();
+                words.dedup();
+                for word in words {
+                    self.postings.entry(word).or_default().push(GlossPosting {
+                        entry_index,
+                        sense_index,
+                        gloss_index,
+                    });
+                }
+            }
+        }
+    }
+    /// Entries having a gloss containing every word of the query.
+    ///
+    /// Results are ranked by exact match first, then by position of the gloss in the entry, then
+    /// by entry priority score.
+    pub fn search(&self, entries: &[JMDictEntry], query: &str) -> Vec<GlossMatch> {
+        let query_words: Vec<String> = tokenize(query).collect();
+        // scan the postings of the rarest query word, and check the other words on the gloss text
+        let rarest_postings = match query_words
+            .iter()
+            .map(|word| self.postings.get(word))
+            .collect::<Option<Vec<&Vec<GlossPosting>>>>()
+            .and_then(|postings| postings.into_iter().min_by_key(|p| p.len()))
+        {
+            Some(postings) => postings,
+            None => return Vec::new(),
+        };
+        let mut best_matches: HashMap<usize, GlossMatch> = HashMap::new();
+        for posting in rarest_postings {
+            let gloss = &entries[posting.entry_index].senses()[posting.sense_index].glosses()
+                [posting.gloss_index];
+            let gloss_words: Vec<String> = tokenize(gloss.text()).collect();
+            if !query_words.iter().all(|word| gloss_words.contains(word)) {
+                continue;
+            }
+            let head_words = gloss_head_words(gloss.text());
+            let exact = head_words == query_words
+                || (head_words.first().is_some_and(|word| word == "to")
+                    && head_words[1..] == query_words[..]);
+            let gloss_match = GlossMatch {
+                entry_index: posting.entry_index,
+                sense_index: posting.sense_index,
+                gloss_index: posting.gloss_index,
+                exact,
+            };
+            let best_match = best_matches
+                .entry(posting.entry_index)
+                .or_insert(gloss_match);
+            if exact && !best_match.exact {
+                *best_match = gloss_match;
+            }
+        }
+        let mut matches: Vec<GlossMatch> = best_matches.values().cloned().collect();
+        matches.sort_by_key(|m| {
+            (
+                !m.exact,
+                m.sense_index,
+                m.gloss_index,
+                std::cmp::Reverse(entries[m.entry_index].priority_score()),
+                m.entry_index,
+            )
+        });
+        matches
+    }
+}
+
+/// Element of a word pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternToken {
+    Char(char),
+    /// `?`: any single character
+    AnyChar,
+    /// `*`: any sequence of characters, possibly empty
+    AnySequence,
+}
+
+/// Literal characters at the start of a token sequence.
+fn leading_chars<'t, I: Iterator<Item = &'t PatternToken>>(tokens: I) -> Vec<char> {
+    tokens
+        .map(|token| match token {
+            PatternToken::Char(c) => Some(*c),
+            _ => None,
+        })
+        .take_while(Option::is_some)
+        .flatten()
+        .collect()
+}
+
+/// Split a pattern into its wildcards and the literal text between them.
+fn pattern_parts(pattern: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut literal_start = 0usize;
+    for (index, c) in pattern.char_indices() {
+        if c == '*' || c == '?' {
+            if literal_start < index {
+                parts.push(&pattern[literal_start..index]);
+            }
+            parts.push(&pattern[index..index + 1]);
+            literal_start = index + 1;
+        }
+    }
+    if literal_start < pattern.len() {
+        parts.push(&pattern[literal_start..]);
+    }
+    parts
+}
+
+/// Pattern over kanji and reading strings, such as `食*`, `*的` or `?べる`.
+///
+/// Full-width `＊` and `？` are accepted as well, so that patterns can be typed without leaving a
+/// Japanese input method. Patterns are normalized like the forms they match: the literal parts
+/// may be typed in katakana, hiragana or romaji.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordPattern {
+    tokens: Vec<PatternToken>,
+}
+
+impl WordPattern {
+    pub fn parse(pattern: &str) -> Self {
+        let pattern = kana::normalize_width(pattern.trim());
+        let mut tokens: Vec<PatternToken> = Vec::with_capacity(pattern.len());
+        for part in pattern_parts(&pattern) {
+            match part {
+                "*" => {
+                    // consecutive `*` are equivalent to a single one
+                    if tokens.last() != Some(&PatternToken::AnySequence) {
+                        tokens.push(PatternToken::AnySequence);
+                    }
+                }
+                "?" => tokens.push(PatternToken::AnyChar),
+                literal => tokens.extend(
+                    kana::normalize_word(literal)
+                        .chars()
+                        .map(PatternToken::Char),
+                ),
+            }
+        }
+        WordPattern { tokens }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+    /// Whether the pattern has no wildcard.
+    pub fn is_literal(&self) -> bool {
+        self.tokens
+            .iter()
+            .all(|token| matches!(token, PatternToken::Char(_)))
+    }
+    /// Characters before the first wildcard.
+    pub fn literal_prefix(&self) -> String {
+        leading_chars(self.tokens.iter()).into_iter().collect()
+    }
+    /// Characters after the last wildcard.
+    pub fn literal_suffix(&self) -> String {
+        let mut suffix = leading_chars(self.tokens.iter().rev());
+        suffix.reverse();
+        suffix.into_iter().collect()
+    }
+    pub fn matches(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        // backtracking glob matching, resuming after the last `*` on mismatch
+        let (mut token_index, mut char_index) = (0usize, 0usize);
+        let mut backtrack: Option<(usize, usize)> = None;
+        while char_index < chars.len() {
+            match self.tokens.get(token_index) {
+                Some(PatternToken::AnySequence) => {
+                    backtrack = Some((token_index + 1, char_index));
+                    token_index += 1;
+                    continue;
+                }
+                Some(PatternToken::AnyChar) => {
+                    token_index += 1;
+                    char_index += 1;
+                    continue;
+                }
+                Some(PatternToken::Char(c)) if *c == chars[char_index] => {
+                    token_index += 1;
+                    char_index += 1;
+                    continue;
+                }
+                _ => (),
+            }
+            match backtrack {
+                Some((sequence_token_index, sequence_char_index)) => {
+                    token_index = sequence_token_index;
+                    char_index = sequence_char_index + 1;
+                    backtrack = Some((sequence_token_index, sequence_char_index + 1));
+                }
+                None => return false,
+            }
+        }
+        self.tokens[token_index..]
+            .iter()
+            .all(|token| *token == PatternToken::AnySequence)
+    }
+}
+
+/// Sorted index of the kanji and reading strings of a dictionary, for pattern searches.
+///
+/// Forms are also indexed reversed, so that patterns with a literal suffix are as fast to search
+/// as patterns with a literal prefix.
+#[derive(Debug, Default, Clone)]
+pub struct FormIndex {
+    forms: BTreeMap<String, Vec<usize>>,
+    reversed_forms: BTreeMap<String, Vec<usize>>,
+}
+
+impl FormIndex {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Index the kanji and readings of an entry, stored at `entry_index` in the dictionary.
+    pub fn add_entry(&mut self, entry_index: usize, entry: &JMDictEntry) {
+        let kanji_forms = entry.kanji().iter().map(|k| k.string());
+        let reading_forms = entry.readings().iter().map(|r| r.string());
+        for form in kanji_forms.chain(reading_forms).map(kana::normalize) {
+            let form_entries = self.forms.entry(form.clone()).or_default();
+            if form_entries.last() != Some(&entry_index) {
+                form_entries.push(entry_index);
+                self.reversed_forms
+                    .entry(form.chars().rev().collect())
+                    .or_default()
+                    .push(entry_index);
+            }
+        }
+    }
+    /// Indices of the entries having a kanji or reading matching a pattern, in dictionary order.
+    pub fn search(&self, pattern: &WordPattern) -> Vec<usize> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        let prefix = pattern.literal_prefix();
+        let suffix = pattern.literal_suffix();
+        let mut entry_indices: Vec<usize> = if prefix.len() >= suffix.len() {
+            Self::matching_entries(&self.forms, &prefix, |form| pattern.matches(form))
+        } else {
+            let reversed_suffix: String = suffix.chars().rev().collect();
+            Self::matching_entries(&self.reversed_forms, &reversed_suffix, |reversed_form| {
+                pattern.matches(&reversed_form.chars().rev().collect::<String>())
+            })
+        };
+        entry_indices.sort();
+        entry_indices.dedup();
+        entry_indices
+    }
+    /// Entries of the forms starting with a prefix and accepted by a filter.
+    fn matching_entries<F: Fn(&str) -> bool>(
+        forms: &BTreeMap<String, Vec<usize>>,
+        prefix: &str,
+        filter: F,
+    ) -> Vec<usize> {
+        forms
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(|(form, _)| form.starts_with(prefix))
+            .filter(|(form, _)| filter(form))
+            .flat_map(|(_, entry_indices)| entry_indices.iter().cloned())
+            .collect()
+    }
+}
+
+/// Page of search results.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SearchPage<T> {
+    total: usize,
+    page: usize,
+    page_size: usize,
+    results: Vec<T>,
+}
+
+impl<T> SearchPage<T> {
+    /// Page `page`, counted from 0, of `page_size` results.
+    pub fn from_results<I: IntoIterator<Item = T>>(
+        results: I,
+        page: usize,
+        page_size: usize,
+    ) -> Self {
+        let mut total = 0usize;
+        let page_start = page.saturating_mul(page_size);
+        let mut page_results = Vec::with_capacity(page_size);
+        for result in results {
+            if total >= page_start && page_results.len() < page_size {
+                page_results.push(result);
+            }
+            total += 1;
+        }
+        SearchPage {
+            total,
+            page,
+            page_size,
+            results: page_results,
+        }
+    }
+    /// Total number of results, over every page.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+    pub fn page(&self) -> usize {
+        self.page
+    }
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+    /// Number of pages, at least 1.
+    pub fn page_count(&self) -> usize {
+        if self.page_size == 0 {
+            1
+        } else {
+            self.total.div_ceil(self.page_size).max(1)
+        }
+    }
+    pub fn results(&self) -> &[T] {
+        &self.results
+    }
+    pub fn into_results(self) -> Vec<T> {
+        self.results
+    }
+    /// Same page with each of its results converted.
+    pub fn map<U, F: FnMut(T) -> U>(self, f: F) -> SearchPage<U> {
+        SearchPage {
+            total: self.total,
+            page: self.page,
+            page_size: self.page_size,
+            results: self.results.into_iter().map(f).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::{Kanji, Priority, Reading, Sense};
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        WordPattern::parse(pattern).matches(text)
+    }
+
+    #[test]
+    fn literal_pattern() {
+        let pattern = WordPattern::parse("食べる");
+        assert!(pattern.is_literal());
+        assert!(pattern.matches("食べる"));
+        assert!(!pattern.matches("食べ"));
+        assert!(!pattern.matches("食べるな"));
+        assert_eq!(pattern.literal_prefix(), "食べる");
+        assert_eq!(pattern.literal_suffix(), "食べる");
+    }
+
+    #[test]
+    fn any_sequence() {
+        assert!(matches("食*", "食"));
+        assert!(matches("食*", "食べる"));
+        assert!(!matches("食*", "飲食"));
+        assert!(matches("*的", "的"));
+        assert!(matches("*的", "具体的"));
+        assert!(!matches("*的", "的確"));
+        assert!(matches("*", ""));
+        assert!(matches("*", "食べる"));
+        assert!(matches("**", "食べる"));
+        assert_eq!(WordPattern::parse("食**る"), WordPattern::parse("食*る"));
+    }
+
+    #[test]
+    fn any_char() {
+        assert!(matches("?べる", "食べる"));
+        assert!(matches("?べる", "すべる"));
+        assert!(!matches("?べる", "べる"));
+        assert!(!matches("?べる", "しらべる"));
+        assert!(matches("??", "学校"));
+        assert!(!matches("??", "学"));
+        assert!(!matches("?", ""));
+    }
+
+    #[test]
+    fn backtracking() {
+        assert!(matches("*る*", "る"));
+        assert!(matches("*る*", "くるま"));
+        assert!(matches("*る*", "するする"));
+        assert!(!matches("*る*", "くま"));
+        assert!(matches("*る", "するする"));
+        assert!(!matches("*る", "するすま"));
+        assert!(matches("*ab*ab", "abcabab"));
+        assert!(matches("*?る", "くる"));
+        assert!(!matches("*?る", "る"));
+        assert!(matches("?*?", "ab"));
+        assert!(!matches("?*?", "a"));
+    }
+
+    #[test]
+    fn normalized_pattern() {
+        assert!(matches("＊ベル", "たべる"));
+        assert!(matches("タ？ル", "たべる"));
+        assert!(matches("ta*", "たべる"));
+        assert_eq!(WordPattern::parse(" 食* ").literal_prefix(), "食");
+    }
+
+    #[test]
+    fn empty_pattern() {
+        let pattern = WordPattern::parse("");
+        assert!(pattern.is_empty());
+        assert!(pattern.is_literal());
+        assert!(pattern.matches(""));
+        assert!(!pattern.matches("食"));
+        assert!(WordPattern::parse("   ").is_empty());
+        assert!(!WordPattern::parse("*").is_empty());
+        assert_eq!(WordPattern::parse("*").literal_prefix(), "");
+        assert_eq!(WordPattern::parse("*").literal_suffix(), "");
+    }
+
+    #[test]
+    fn literal_affixes() {
+        let pattern = WordPattern::parse("食*物");
+        assert!(!pattern.is_literal());
+        assert_eq!(pattern.literal_prefix(), "食");
+        assert_eq!(pattern.literal_suffix(), "物");
+        let pattern = WordPattern::parse("?べ*る");
+        assert_eq!(pattern.literal_prefix(), "");
+        assert_eq!(pattern.literal_suffix(), "る");
+    }
+
+    fn form_index(forms: &[(&str, &str)]) -> FormIndex {
+        let mut index = FormIndex::new();
+        for (entry_index, &(kanji, reading)) in forms.iter().enumerate() {
+            let entry = JMDictEntry::new(
+                entry_index as u32,
+                vec![Kanji::new(String::from(kanji))],
+                vec![Reading::new(String::from(reading))],
+                Vec::new(),
+            );
+            index.add_entry(entry_index, &entry);
+        }
+        index
+    }
+
+    fn search(index: &FormIndex, pattern: &str) -> Vec<usize> {
+        index.search(&WordPattern::parse(pattern))
+    }
+
+    #[test]
+    fn form_index_ranges() {
+        let index = form_index(&[
+            ("食べる", "たべる"),
+            ("食物", "しょくもつ"),
+            ("飲食", "いんしょく"),
+            ("具体的", "ぐたいてき"),
+            ("的", "まと"),
+            ("調べる", "しらべる"),
+            ("食", "しょく"),
+        ]);
+        // prefix range
+        assert_eq!(search(&index, "食*"), vec![0, 1, 6]);
+        assert_eq!(search(&index, "しょく*"), vec![1, 6]);
+        // suffix range, over reversed forms
+        assert_eq!(search(&index, "*的"), vec![3, 4]);
+        assert_eq!(search(&index, "*食"), vec![2, 6]);
+        assert_eq!(search(&index, "*べる"), vec![0, 5]);
+        // longest of the prefix and suffix
+        assert_eq!(search(&index, "食*物"), vec![1]);
+        assert_eq!(search(&index, "し*べる"), vec![5]);
+        // no literal affix, whole index
+        assert_eq!(search(&index, "?べる"), vec![0, 5]);
+        assert_eq!(search(&index, "??べる"), vec![5]);
+        assert_eq!(search(&index, "*る*"), vec![0, 5]);
+        // kanji and reading of an entry are both searched, once
+        assert_eq!(search(&index, "*"), vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(search(&index, "食"), vec![6]);
+        assert!(search(&index, "").is_empty());
+        assert!(search(&index, "飲*物").is_empty());
+    }
+
+    /// Entry with a sense for each list of glosses, and the given priority codes on its kanji.
+    fn gloss_entry(senses: &[&[&str]], priorities: &[Priority]) -> JMDictEntry {
+        let mut kanji = Kanji::new(String::from("字"));
+        for priority in priorities {
+            kanji.add_priority(*priority);
+        }
+        let senses = senses
+            .iter()
+            .map(|glosses| {
+                let mut sense = Sense::new();
+                for gloss in glosses.iter() {
+                    sense.add_gloss(Gloss::new(String::from(*gloss)));
+                }
+                sense
+            })
+            .collect();
+        JMDictEntry::new(0, vec![kanji], Vec::new(), senses)
+    }
+
+    fn gloss_matches(entries: &[JMDictEntry], query: &str) -> Vec<GlossMatch> {
+        let mut index = GlossIndex::new();
+        for (entry_index, entry) in entries.iter().enumerate() {
+            index.add_entry(entry_index, entry);
+        }
+        index.search(entries, query)
+    }
+
+    fn gloss_search(entries: &[JMDictEntry], query: &str) -> Vec<(usize, bool)> {
+        gloss_matches(entries, query)
+            .iter()
+            .map(|m| (m.entry_index(), m.exact()))
+            .collect()
+    }
+
+    #[test]
+    fn gloss_exact_before_partial() {
+        let entries = [
+            gloss_entry(&[&["eat quickly"]], &[Priority::News(1), Priority::Ichi(1)]),
+            gloss_entry(&[&["eat"]], &[]),
+            gloss_entry(&[&["the EAT (food)"]], &[]),
+        ];
+        assert_eq!(
+            gloss_search(&entries, "eat"),
+            vec![(1, true), (0, false), (2, false)]
+        );
+        // case, punctuation and parenthesized notes are ignored
+        assert_eq!(gloss_search(&entries, "The eat!"), vec![(2, true)]);
+        assert!(gloss_search(&entries, "drink").is_empty());
+    }
+
+    #[test]
+    fn gloss_verb_prefix() {
+        let entries = [
+            gloss_entry(&[&["go out"]], &[]),
+            gloss_entry(&[&["to go"]], &[]),
+            gloss_entry(&[&["go (board game)"]], &[]),
+        ];
+        // the leading "to" of verbs is optional for an exact match
+        assert_eq!(
+            gloss_search(&entries, "go"),
+            vec![(1, true), (2, true), (0, false)]
+        );
+        assert_eq!(gloss_search(&entries, "to go"), vec![(1, true)]);
+    }
+
+    #[test]
+    fn gloss_earlier_senses_first() {
+        let entries = [
+            gloss_entry(&[&["letter"], &["postage stamp", "stamp"]], &[]),
+            gloss_entry(&[&["stamp"]], &[]),
+            gloss_entry(&[&["seal", "stamp"]], &[]),
+        ];
+        // by sense, then by gloss within the sense
+        assert_eq!(
+            gloss_search(&entries, "stamp"),
+            vec![(1, true), (2, true), (0, true)]
+        );
+        // the first matching gloss of an entry is kept, unless a later one is exact
+        let positions: Vec<(usize, usize, usize)> = gloss_matches(&entries, "stamp")
+            .iter()
+            .map(|m| (m.entry_index(), m.sense_index(), m.gloss_index()))
+            .collect();
+        assert_eq!(positions, vec![(1, 0, 0), (2, 0, 1), (0, 1, 1)]);
+    }
+
+    #[test]
+    fn gloss_priority_tie_break() {
+        let entries = [
+            gloss_entry(&[&["cat"]], &[]),
+            gloss_entry(&[&["cat"]], &[Priority::Ichi(2)]),
+            gloss_entry(&[&["cat"]], &[Priority::Ichi(1), Priority::Frequency(10)]),
+            gloss_entry(&[&["cat"]], &[]),
+        ];
+        // most common first, then in dictionary order
+        assert_eq!(
+            gloss_search(&entries, "cat"),
+            vec![(2, true), (1, true), (0, true), (3, true)]
+        );
+    }
+
+    #[test]
+    fn page_count() {
+        assert_eq!(SearchPage::from_results(0..0, 0, 20).page_count(), 1);
+        assert_eq!(SearchPage::from_results(0..20, 0, 20).page_count(), 1);
+        assert_eq!(SearchPage::from_results(0..21, 0, 20).page_count(), 2);
+        assert_eq!(SearchPage::from_results(0..5, 0, 0).page_count(), 1);
+        let page = SearchPage::from_results(0..45, 2, 20);
+        assert_eq!(page.total(), 45);
+        assert_eq!(page.results(), &[40, 41, 42, 43, 44]);
+    }
+}
@@ -0,0 +1,478 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Entity definitions from the JMdict DOCTYPE, mapping codes such as `v5u` to their description.
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq)]
+pub struct Entities(BTreeMap<String, String>);
+
+impl Entities {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Read the `<!ENTITY name "description">` declarations of a DOCTYPE.
+    ///
+    /// Character references in descriptions are expanded, once in the declaration and once more
+    /// where the entity would be used, so that `&#38;#38;` is read as `&`.
+    pub fn from_doctype(doctype_buffer: &[u8]) -> Self {
+        use regex::bytes::Regex;
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r#"<!ENTITY ([\w-]+) "([^"]*)">"#).unwrap();
+        }
+        let mut entities = Self::new();
+        for captures in RE.captures_iter(doctype_buffer) {
+            let name = String::from_utf8_lossy(&captures[1]);
+            let value = String::from_utf8_lossy(&captures[2]);
+            let description = unescape_references(&unescape_references(&value, false), true);
+            entities.insert(name.into_owned(), description);
+        }
+        entities
+    }
+    pub fn insert(&mut self, name: String, description: String) {
+        self.0.insert(name, description);
+    }
+    /// Description of an entity code.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(|s| s as &str)
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k as &str, v as &str))
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Expand the character references of XML text, such as `&#38;`, and the predefined entity
+/// references such as `&amp;` if asked. Other references are kept as is.
+fn unescape_references(text: &str, predefined_entities: bool) -> String {
+    use regex::{Captures, Regex};
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"&(#[0-9]+|#x[0-9A-Fa-f]+|amp|lt|gt|quot|apos);").unwrap();
+    }
+    RE.replace_all(text, |captures: &Captures| {
+        let reference = &captures[1];
+        let c = if let Some(hexadecimal) = reference.strip_prefix("#x") {
+            u32::from_str_radix(hexadecimal, 16)
+                .ok()
+                .and_then(std::char::from_u32)
+        } else if let Some(decimal) = reference.strip_prefix('#') {
+            decimal.parse().ok().and_then(std::char::from_u32)
+        } else if predefined_entities {
+            match reference {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                _ => Some('\''),
+            }
+        } else {
+            None
+        };
+        c.map(String::from)
+            .unwrap_or_else(|| String::from(&captures[0]))
+    })
+    .into_owned()
+}
+
+/// Declare a tag enum mapping JMdict entity codes to variants.
+///
+/// Codes unknown to this crate, for example from a newer dictionary release, are kept in an
+/// `Other` variant. Tags are serialized as their code.
+macro_rules! jmdict_tags {
+    ($(#[$meta:meta])* $name:ident { $($(#[$variant_meta:meta])* $variant:ident => $code:expr,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            Other(String),
+        }
+
+        impl $name {
+            pub fn from_code(code: &str) -> Self {
+                match code {
+                    $($code => $name::$variant,)*
+                    other => $name::Other(String::from(other)),
+                }
+            }
+            /// Entity code of the tag, such as `v5u`.
+            pub fn code(&self) -> &str {
+                match self {
+                    $($name::$variant => $code,)*
+                    $name::Other(code) => code,
+                }
+            }
+            /// Human-readable description of the tag, as defined by the dictionary file.
+            pub fn description<'e>(&self, entities: &'e Entities) -> Option<&'e str> {
+                entities.get(self.code())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.code())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let code = String::deserialize(deserializer)?;
+                Ok($name::from_code(&code))
+            }
+        }
+    };
+}
+
+jmdict_tags! {
+    /// Kanji element information from `<ke_inf>`.
+    KanjiInfo {
+        Ateji => "ateji",
+        IrregularKana => "ik",
+        IrregularKanji => "iK",
+        IrregularOkurigana => "io",
+        OutdatedKanji => "oK",
+        RareKanji => "rK",
+        SearchOnlyKanji => "sK",
+    }
+}
+
+impl KanjiInfo {
+    /// Whether the tag marks an irregular, out-dated, rarely-used or search-only spelling.
+    pub fn is_rare(&self) -> bool {
+        matches!(
+            self,
+            KanjiInfo::IrregularKana
+                | KanjiInfo::IrregularKanji
+                | KanjiInfo::IrregularOkurigana
+                | KanjiInfo::OutdatedKanji
+                | KanjiInfo::RareKanji
+                | KanjiInfo::SearchOnlyKanji
+        )
+    }
+}
+
+jmdict_tags! {
+    /// Reading element information from `<re_inf>`.
+    ReadingInfo {
+        Gikun => "gikun",
+        IrregularKana => "ik",
+        OutdatedKana => "ok",
+        RareKana => "rk",
+        SearchOnlyKana => "sk",
+        UsuallyKanji => "uK",
+    }
+}
+
+impl ReadingInfo {
+    /// Whether the tag marks an irregular, out-dated, rarely-used or search-only reading.
+    pub fn is_rare(&self) -> bool {
+        matches!(
+            self,
+            ReadingInfo::IrregularKana
+                | ReadingInfo::OutdatedKana
+                | ReadingInfo::RareKana
+                | ReadingInfo::SearchOnlyKana
+        )
+    }
+}
+
+jmdict_tags! {
+    /// Part of speech from `<pos>`.
+    PartOfSpeech {
+        AdjectiveF => "adj-f",
+        AdjectiveI => "adj-i",
+        AdjectiveIx => "adj-ix",
+        AdjectiveKari => "adj-kari",
+        AdjectiveKu => "adj-ku",
+        AdjectiveNa => "adj-na",
+        AdjectiveNari => "adj-nari",
+        AdjectiveNo => "adj-no",
+        AdjectivePrenoun => "adj-pn",
+        AdjectiveShiku => "adj-shiku",
+        AdjectiveTaru => "adj-t",
+        Adverb => "adv",
+        AdverbTo => "adv-to",
+        Auxiliary => "aux",
+        AuxiliaryAdjective => "aux-adj",
+        AuxiliaryVerb => "aux-v",
+        Conjunction => "conj",
+        Copula => "cop",
+        Counter => "ctr",
+        Expression => "exp",
+        Interjection => "int",
+        Noun => "n",
+        NounAdverbial => "n-adv",
+        NounProper => "n-pr",
+        NounPrefix => "n-pref",
+        NounSuffix => "n-suf",
+        NounTemporal => "n-t",
+        Numeric => "num",
+        Pronoun => "pn",
+        Prefix => "pref",
+        Particle => "prt",
+        Suffix => "suf",
+        Unclassified => "unc",
+        VerbUnspecified => "v-unspec",
+        Ichidan => "v1",
+        IchidanKureru => "v1-s",
+        NidanAS => "v2a-s",
+        NidanBK => "v2b-k",
+        NidanBS => "v2b-s",
+        NidanDK => "v2d-k",
+        NidanDS => "v2d-s",
+        NidanGK => "v2g-k",
+        NidanGS => "v2g-s",
+        NidanHK => "v2h-k",
+        NidanHS => "v2h-s",
+        NidanKK => "v2k-k",
+        NidanKS => "v2k-s",
+        NidanMK => "v2m-k",
+        NidanMS => "v2m-s",
+        NidanNS => "v2n-s",
+        NidanRK => "v2r-k",
+        NidanRS => "v2r-s",
+        NidanSS => "v2s-s",
+        NidanTK => "v2t-k",
+        NidanTS => "v2t-s",
+        NidanWS => "v2w-s",
+        NidanYK => "v2y-k",
+        NidanYS => "v2y-s",
+        NidanZS => "v2z-s",
+        YodanBu => "v4b",
+        YodanGu => "v4g",
+        YodanHu => "v4h",
+        YodanKu => "v4k",
+        YodanMu => "v4m",
+        YodanNu => "v4n",
+        YodanRu => "v4r",
+        YodanSu => "v4s",
+        YodanTsu => "v4t",
+        GodanAru => "v5aru",
+        GodanBu => "v5b",
+        GodanGu => "v5g",
+        GodanKu => "v5k",
+        GodanIku => "v5k-s",
+        GodanMu => "v5m",
+        GodanNu => "v5n",
+        GodanRu => "v5r",
+        GodanRuIrregular => "v5r-i",
+        GodanSu => "v5s",
+        GodanTsu => "v5t",
+        GodanU => "v5u",
+        GodanUSpecial => "v5u-s",
+        GodanUru => "v5uru",
+        Intransitive => "vi",
+        Kuru => "vk",
+        IrregularNu => "vn",
+        IrregularRu => "vr",
+        Suru => "vs",
+        SuruClassical => "vs-c",
+        SuruIncluded => "vs-i",
+        SuruSpecial => "vs-s",
+        Transitive => "vt",
+        Zuru => "vz",
+    }
+}
+
+jmdict_tags! {
+    /// Field of application from `<field>`.
+    Field {
+        Agriculture => "agric",
+        Anatomy => "anat",
+        Archeology => "archeol",
+        Architecture => "archit",
+        Art => "art",
+        Astronomy => "astron",
+        AudioVisual => "audvid",
+        Aviation => "aviat",
+        Baseball => "baseb",
+        Biochemistry => "biochem",
+        Biology => "biol",
+        Botany => "bot",
+        Buddhism => "Buddh",
+        Business => "bus",
+        Chemistry => "chem",
+        Christianity => "Christn",
+        Clothing => "cloth",
+        Computing => "comp",
+        Crystallography => "cryst",
+        Ecology => "ecol",
+        Economics => "econ",
+        Electricity => "elec",
+        Electronics => "electr",
+        Embryology => "embryo",
+        Engineering => "engr",
+        Entomology => "ent",
+        Finance => "finc",
+        Fishing => "fish",
+        Food => "food",
+        Gardening => "gardn",
+        Genetics => "genet",
+        Geography => "geogr",
+        Geology => "geol",
+        Geometry => "geom",
+        Go => "go",
+        Golf => "golf",
+        Grammar => "gramm",
+        GreekMythology => "grmyth",
+        Hanafuda => "hanaf",
+        HorseRacing => "horse",
+        Law => "law",
+        Linguistics => "ling",
+        Logic => "logic",
+        MartialArts => "MA",
+        Mahjong => "mahj",
+        Mathematics => "math",
+        MechanicalEngineering => "mech",
+        Medicine => "med",
+        Meteorology => "met",
+        Military => "mil",
+        Music => "music",
+        Ornithology => "ornith",
+        Paleontology => "paleo",
+        Pathology => "pathol",
+        Pharmacy => "pharm",
+        Philosophy => "phil",
+        Photography => "photo",
+        Physics => "physics",
+        Physiology => "physiol",
+        Printing => "print",
+        Psychiatry => "psy",
+        Psychology => "psych",
+        Railway => "rail",
+        Shinto => "Shinto",
+        Shogi => "shogi",
+        Sports => "sports",
+        Statistics => "stat",
+        Sumo => "sumo",
+        Telecommunications => "telec",
+        Trademark => "tradem",
+        VideoGames => "vidg",
+        Zoology => "zool",
+    }
+}
+
+jmdict_tags! {
+    /// Miscellaneous sense information from `<misc>`.
+    Misc {
+        Abbreviation => "abbr",
+        Archaism => "arch",
+        Character => "char",
+        ChildrensLanguage => "chn",
+        Colloquialism => "col",
+        Company => "company",
+        Creature => "creat",
+        Dated => "dated",
+        Deity => "dei",
+        Derogatory => "derog",
+        Document => "doc",
+        Euphemistic => "euph",
+        Event => "ev",
+        Familiar => "fam",
+        Female => "fem",
+        Fiction => "fict",
+        Formal => "form",
+        GivenName => "given",
+        Group => "group",
+        Historical => "hist",
+        Honorific => "hon",
+        Humble => "hum",
+        Idiomatic => "id",
+        Jocular => "joc",
+        Legend => "leg",
+        MangaSlang => "m-sl",
+        Male => "male",
+        Mythology => "myth",
+        InternetSlang => "net-sl",
+        Object => "obj",
+        Obsolete => "obs",
+        Obscure => "obsc",
+        Onomatopoeia => "on-mim",
+        Organization => "organization",
+        OtherName => "oth",
+        Person => "person",
+        Place => "place",
+        Poetical => "poet",
+        Polite => "pol",
+        Product => "product",
+        Proverb => "proverb",
+        Quotation => "quote",
+        Rare => "rare",
+        Religion => "relig",
+        Sensitive => "sens",
+        Service => "serv",
+        Ship => "ship",
+        Slang => "sl",
+        Station => "station",
+        Surname => "surname",
+        UsuallyKana => "uk",
+        Unclassified => "unclass",
+        Vulgar => "vulg",
+        Work => "work",
+        Rude => "X",
+        Yojijukugo => "yoji",
+    }
+}
+
+jmdict_tags! {
+    /// Dialect from `<dial>`.
+    Dialect {
+        Brazilian => "bra",
+        Hokkaido => "hob",
+        Kansai => "ksb",
+        Kantou => "ktb",
+        Kyoto => "kyb",
+        Kyuushuu => "kyu",
+        Nagano => "nab",
+        Osaka => "osb",
+        Ryuukyuu => "rkb",
+        Touhoku => "thb",
+        Tosa => "tsb",
+        Tsugaru => "tsug",
+    }
+}
+
+jmdict_tags! {
+    /// Kind of proper name, from the JMnedict `<name_type>`.
+    NameType {
+        Character => "char",
+        Company => "company",
+        Creature => "creat",
+        Deity => "dei",
+        Document => "doc",
+        Event => "ev",
+        Female => "fem",
+        Fiction => "fict",
+        Given => "given",
+        Group => "group",
+        Legend => "leg",
+        Male => "masc",
+        Mythology => "myth",
+        Object => "obj",
+        Organization => "organization",
+        OtherName => "oth",
+        Person => "person",
+        Place => "place",
+        Product => "product",
+        Religion => "relig",
+        Service => "serv",
+        Ship => "ship",
+        Station => "station",
+        Surname => "surname",
+        Unclassified => "unclass",
+        Work => "work",
+    }
+}
+
+jmdict_tags! {
+    /// Kind of gloss, from the `g_type` attribute of `<gloss>`.
+    GlossType {
+        Explanation => "expl",
+        Figurative => "fig",
+        Literal => "lit",
+        Trademark => "tm",
+    }
+}
@@ -0,0 +1,340 @@
+//! Serialization of entries back into JMdict XML, for example to write a filtered dictionary or to
+//! merge custom entries with JMdict.
+//!
+//! Elements are written one per line without indentation, as in the JMdict files, and tags are
+//! written as references to the entities declared in the DOCTYPE, such as `<pos>&n;</pos>`.
+
+use super::entry::{CrossReference, Gloss, JMDictEntry, Kanji, LanguageSource, Reading, Sense};
+use super::tags::Entities;
+use super::Error;
+use std::borrow::Borrow;
+use std::io::Write;
+
+/// Element and attribute declarations of the JMdict DTD.
+const JMDICT_DTD: &str = r#"<!ELEMENT JMdict (entry*)>
+<!ELEMENT entry (ent_seq, k_ele*, r_ele+, sense+)>
+<!ELEMENT ent_seq (#PCDATA)>
+<!ELEMENT k_ele (keb, ke_inf*, ke_pri*)>
+<!ELEMENT keb (#PCDATA)>
+<!ELEMENT ke_inf (#PCDATA)>
+<!ELEMENT ke_pri (#PCDATA)>
+<!ELEMENT r_ele (reb, re_nokanji?, re_restr*, re_inf*, re_pri*)>
+<!ELEMENT reb (#PCDATA)>
+<!ELEMENT re_nokanji (#PCDATA)>
+<!ELEMENT re_restr (#PCDATA)>
+<!ELEMENT re_inf (#PCDATA)>
+<!ELEMENT re_pri (#PCDATA)>
+<!ELEMENT sense (stagk*, stagr*, pos*, xref*, ant*, field*, misc*, s_inf*, lsource*, dial*, gloss*)>
+<!ELEMENT stagk (#PCDATA)>
+<!ELEMENT stagr (#PCDATA)>
+<!ELEMENT xref (#PCDATA)*>
+<!ELEMENT ant (#PCDATA)*>
+<!ELEMENT pos (#PCDATA)>
+<!ELEMENT field (#PCDATA)>
+<!ELEMENT misc (#PCDATA)>
+<!ELEMENT lsource (#PCDATA)>
+<!ATTLIST lsource xml:lang CDATA "eng">
+<!ATTLIST lsource ls_type CDATA #IMPLIED>
+<!ATTLIST lsource ls_wasei CDATA #IMPLIED>
+<!ELEMENT dial (#PCDATA)>
+<!ELEMENT gloss (#PCDATA | pri)*>
+<!ATTLIST gloss xml:lang CDATA "eng">
+<!ATTLIST gloss g_gend CDATA #IMPLIED>
+<!ATTLIST gloss g_type CDATA #IMPLIED>
+<!ELEMENT pri (#PCDATA)>
+<!ELEMENT s_inf (#PCDATA)>
+"#;
+
+/// Writer of a JMdict XML file, entry by entry.
+///
+/// The XML declaration and the DOCTYPE are written on creation, and the closing `</JMdict>` tag by
+/// `finish`. Output is written in small pieces, so files should be wrapped in a `BufWriter`.
+pub struct JMDictWriter<W: Write> {
+    writer: W,
+    entities: Entities,
+}
+
+impl<W: Write> JMDictWriter<W> {
+    /// Start a JMdict file declaring the given entities.
+    ///
+    /// Every tag of the entries written afterwards must be one of these entities.
+    pub fn new(mut writer: W, entities: &Entities) -> Result<Self, Error> {
+        writer.write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")?;
+        writer.write_all(b"<!DOCTYPE JMdict [\n")?;
+        writer.write_all(JMDICT_DTD.as_bytes())?;
+        for (name, description) in entities.iter() {
+            writeln!(
+                writer,
+                "<!ENTITY {} \"{}\">",
+                name,
+                escape_entity_value(description)
+            )?;
+        }
+        writer.write_all(b"]>\n<JMdict>\n")?;
+        Ok(JMDictWriter {
+            writer,
+            entities: entities.clone(),
+        })
+    }
+    /// Write an entry, failing without writing anything if one of its tags is not a declared
+    /// entity.
+    pub fn write_entry(&mut self, entry: &JMDictEntry) -> Result<(), Error> {
+        if let Some(code) = entity_codes(entry)
+            .into_iter()
+            .find(|code| self.entities.get(code).is_none())
+        {
+            return Err(Error::from(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "entity &{}; of entry {} is not declared",
+                    code,
+                    entry.sequence()
+                ),
+            )));
+        }
+        let mut entry_xml = String::new();
+        push_entry(&mut entry_xml, entry);
+        self.writer.write_all(entry_xml.as_bytes())?;
+        Ok(())
+    }
+    /// Close the `<JMdict>` element and return the underlying writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.writer.write_all(b"</JMdict>\n")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Write a complete JMdict file.
+///
+/// Tags of the entries missing from the given entities are declared with their code as
+/// description, so that the file stays valid.
+pub fn write_jmdict<W: Write, E: Borrow<JMDictEntry>>(
+    writer: W,
+    entities: &Entities,
+    entries: &[E],
+) -> Result<W, Error> {
+    let mut all_entities = entities.clone();
+    for entry in entries {
+        for code in entity_codes(entry.borrow()) {
+            if all_entities.get(code).is_none() {
+                all_entities.insert(String::from(code), String::from(code));
+            }
+        }
+    }
+    let mut jmdict_writer = JMDictWriter::new(writer, &all_entities)?;
+    for entry in entries {
+        jmdict_writer.write_entry(entry.borrow())?;
+    }
+    jmdict_writer.finish()
+}
+
+/// Codes of the tags of an entry, written as entity references.
+fn entity_codes(entry: &JMDictEntry) -> Vec<&str> {
+    let kanji_codes = entry
+        .kanji()
+        .iter()
+        .flat_map(|kanji| kanji.info().iter().map(|info| info.code()));
+    let reading_codes = entry
+        .readings()
+        .iter()
+        .flat_map(|reading| reading.info().iter().map(|info| info.code()));
+    let sense_codes = entry.senses().iter().flat_map(|sense| {
+        sense
+            .parts_of_speech()
+            .iter()
+            .map(|pos| pos.code())
+            .chain(sense.fields().iter().map(|field| field.code()))
+            .chain(sense.misc().iter().map(|misc| misc.code()))
+            .chain(sense.dialects().iter().map(|dialect| dialect.code()))
+    });
+    kanji_codes
+        .chain(reading_codes)
+        .chain(sense_codes)
+        .collect()
+}
+
+/// Escape the text of an element or the value of an attribute.
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escape an entity description, in which `%` would start a parameter entity reference.
+///
+/// Character references of an entity value are expanded when the entity is declared, and the
+/// result is parsed again where the entity is used: `&`, `%` and `<` are escaped twice, such as
+/// `&#38;#38;` for `&` (XML 1.0, appendix D). `>` is escaped too, so that angle brackets stay
+/// balanced within the DOCTYPE.
+fn escape_entity_value(value: &str) -> String {
+    value
+        .replace('&', "&#38;#38;")
+        .replace('%', "&#38;#37;")
+        .replace('<', "&#38;#60;")
+        .replace('>', "&#62;")
+        .replace('"', "&#34;")
+}
+
+fn push_element(xml: &mut String, name: &str, text: &str) {
+    xml.push_str(&format!("<{0}>{1}</{0}>\n", name, escape_text(text)));
+}
+
+fn push_entity_element(xml: &mut String, name: &str, code: &str) {
+    xml.push_str(&format!("<{0}>&{1};</{0}>\n", name, code));
+}
+
+fn push_entry(xml: &mut String, entry: &JMDictEntry) {
+    xml.push_str("<entry>\n");
+    push_element(xml, "ent_seq", &entry.sequence().to_string());
+    for kanji in entry.kanji() {
+        push_kanji(xml, kanji);
+    }
+    for reading in entry.readings() {
+        push_reading(xml, reading);
+    }
+    for sense in entry.senses() {
+        push_sense(xml, sense);
+    }
+    xml.push_str("</entry>\n");
+}
+
+fn push_kanji(xml: &mut String, kanji: &Kanji) {
+    xml.push_str("<k_ele>\n");
+    push_element(xml, "keb", kanji.string());
+    for info in kanji.info() {
+        push_entity_element(xml, "ke_inf", info.code());
+    }
+    for priority in kanji.priority() {
+        push_element(xml, "ke_pri", &priority.code());
+    }
+    xml.push_str("</k_ele>\n");
+}
+
+fn push_reading(xml: &mut String, reading: &Reading) {
+    xml.push_str("<r_ele>\n");
+    push_element(xml, "reb", reading.string());
+    if reading.no_kanji() {
+        xml.push_str("<re_nokanji/>\n");
+    }
+    for restriction in reading.restrictions() {
+        push_element(xml, "re_restr", restriction);
+    }
+    for info in reading.info() {
+        push_entity_element(xml, "re_inf", info.code());
+    }
+    for priority in reading.priority() {
+        push_element(xml, "re_pri", &priority.code());
+    }
+    xml.push_str("</r_ele>\n");
+}
+
+fn push_sense(xml: &mut String, sense: &Sense) {
+    xml.push_str("<sense>\n");
+    for kanji_restriction in sense.kanji_restrictions() {
+        push_element(xml, "stagk", kanji_restriction);
+    }
+    for reading_restriction in sense.reading_restrictions() {
+        push_element(xml, "stagr", reading_restriction);
+    }
+    for part_of_speech in sense.parts_of_speech() {
+        push_entity_element(xml, "pos", part_of_speech.code());
+    }
+    for cross_reference in sense.cross_references() {
+        push_element(xml, "xref", &reference_text(cross_reference));
+    }
+    for antonym in sense.antonyms() {
+        push_element(xml, "ant", &reference_text(antonym));
+    }
+    for field in sense.fields() {
+        push_entity_element(xml, "field", field.code());
+    }
+    for misc in sense.misc() {
+        push_entity_element(xml, "misc", misc.code());
+    }
+    for info in sense.info() {
+        push_element(xml, "s_inf", info);
+    }
+    for language_source in sense.language_sources() {
+        push_language_source(xml, language_source);
+    }
+    for dialect in sense.dialects() {
+        push_entity_element(xml, "dial", dialect.code());
+    }
+    for gloss in sense.glosses() {
+        push_gloss(xml, gloss);
+    }
+    xml.push_str("</sense>\n");
+}
+
+/// Reference as written in `<xref>` and `<ant>`, such as `来る・くる・1`.
+fn reference_text(reference: &CrossReference) -> String {
+    let mut text = String::from(reference.text());
+    if let Some(reading) = reference.reading() {
+        text.push('・');
+        text.push_str(reading);
+    }
+    if let Some(sense) = reference.sense() {
+        text.push('・');
+        text.push_str(&sense.to_string());
+    }
+    text
+}
+
+/// Attributes of a start tag, such as ` xml:lang="ger"`, skipping those without a value.
+fn attributes_string(attributes: &[(&str, Option<&str>)]) -> String {
+    attributes
+        .iter()
+        .filter_map(|&(name, value)| {
+            value.map(|value| format!(" {}=\"{}\"", name, escape_text(value)))
+        })
+        .collect()
+}
+
+fn push_language_source(xml: &mut String, language_source: &LanguageSource) {
+    let attributes = attributes_string(&[
+        ("xml:lang", language_source.lang()),
+        (
+            "ls_type",
+            if language_source.partial() {
+                Some("part")
+            } else {
+                None
+            },
+        ),
+        (
+            "ls_wasei",
+            if language_source.wasei() {
+                Some("y")
+            } else {
+                None
+            },
+        ),
+    ]);
+    if language_source.text().is_empty() {
+        xml.push_str(&format!("<lsource{}/>\n", attributes));
+    } else {
+        xml.push_str(&format!(
+            "<lsource{}>{}</lsource>\n",
+            attributes,
+            escape_text(language_source.text())
+        ));
+    }
+}
+
+fn push_gloss(xml: &mut String, gloss: &Gloss) {
+    let attributes = attributes_string(&[
+        ("xml:lang", gloss.lang()),
+        (
+            "g_type",
+            gloss.gloss_type().map(|gloss_type| gloss_type.code()),
+        ),
+        ("g_gend", gloss.gender()),
+    ]);
+    xml.push_str(&format!(
+        "<gloss{}>{}</gloss>\n",
+        attributes,
+        escape_text(gloss.text())
+    ));
+}
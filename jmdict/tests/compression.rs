@@ -0,0 +1,87 @@
+#![cfg(feature = "reader")]
+
+use jmdict::compression::Compression;
+use jmdict::prelude::*;
+use std::io::Write;
+
+const SAMPLE: &[u8] = include_bytes!("data/jmdict.xml");
+
+/// Sequence numbers of the entries of a file, read through format detection.
+fn read_sequences(data: &[u8]) -> Result<Vec<u32>, jmdict::Error> {
+    JMDictReader::from_any(data)?
+        .map(|entry| entry.map(|entry| entry.sequence()))
+        .collect()
+}
+
+fn sample_sequences() -> Vec<u32> {
+    read_sequences(SAMPLE).unwrap()
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn detect() {
+    assert_eq!(Compression::detect(&gzip(SAMPLE)), Compression::Gzip);
+    assert_eq!(
+        Compression::detect(&[0xFD, b'7', b'z', b'X', b'Z', 0x00, 0x00, 0x04]),
+        Compression::Xz
+    );
+    assert_eq!(
+        Compression::detect(&[0x28, 0xB5, 0x2F, 0xFD, 0x24]),
+        Compression::Zstd
+    );
+    assert_eq!(Compression::detect(SAMPLE), Compression::None);
+    // too short to hold a magic number
+    assert_eq!(Compression::detect(&[0x1F]), Compression::None);
+    assert_eq!(Compression::detect(&[]), Compression::None);
+}
+
+#[test]
+fn plain_and_gzip() {
+    assert_eq!(sample_sequences().len(), 12);
+    assert_eq!(read_sequences(&gzip(SAMPLE)).unwrap(), sample_sequences());
+    let jmdict = JMDict::from_jmdict_any(&gzip(SAMPLE)[..]).unwrap();
+    assert_eq!(jmdict.len(), 12);
+}
+
+#[cfg(feature = "xz2")]
+#[test]
+fn xz() {
+    let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(SAMPLE).unwrap();
+    let data = encoder.finish().unwrap();
+    assert_eq!(Compression::detect(&data), Compression::Xz);
+    assert_eq!(read_sequences(&data).unwrap(), sample_sequences());
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd() {
+    let data = zstd::stream::encode_all(SAMPLE, 3).unwrap();
+    assert_eq!(Compression::detect(&data), Compression::Zstd);
+    assert_eq!(read_sequences(&data).unwrap(), sample_sequences());
+}
+
+#[cfg(not(feature = "xz2"))]
+#[test]
+fn xz_unsupported() {
+    let data = [0xFD, b'7', b'z', b'X', b'Z', 0x00, 0x00, 0x04];
+    match read_sequences(&data) {
+        Err(jmdict::Error::UnsupportedCompression(Compression::Xz)) => (),
+        other => panic!("expected unsupported compression, got {:?}", other),
+    }
+}
+
+#[cfg(not(feature = "zstd"))]
+#[test]
+fn zstd_unsupported() {
+    let data = [0x28, 0xB5, 0x2F, 0xFD, 0x24];
+    match read_sequences(&data) {
+        Err(jmdict::Error::UnsupportedCompression(Compression::Zstd)) => (),
+        other => panic!("expected unsupported compression, got {:?}", other),
+    }
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "reader")]
+
+use jmdict::deinflect::{deinflect, Inflection};
+use jmdict::prelude::*;
+
+fn sample_jmdict() -> JMDict {
+    JMDict::from_jmdict(&include_bytes!("data/jmdict.xml")[..]).unwrap()
+}
+
+fn entry<'j>(jmdict: &'j JMDict, kanji: &str) -> &'j JMDictEntry {
+    jmdict.by_kanji(kanji).next().unwrap()
+}
+
+/// Whether one of the candidates of an inflected word is the dictionary form of an entry.
+fn matches_entry(word: &str, dictionary_form: &str, entry: &JMDictEntry) -> bool {
+    deinflect(word)
+        .iter()
+        .filter(|deinflection| deinflection.word() == dictionary_form)
+        .any(|deinflection| !deinflection.is_original() && deinflection.matches_entry(entry))
+}
+
+fn lookup_inflected(jmdict: &JMDict, word: &str) -> (Vec<u32>, Vec<Inflection>) {
+    let (entries, inflections) = jmdict.lookup_inflected(word);
+    (
+        entries.into_iter().map(JMDictEntry::sequence).collect(),
+        inflections,
+    )
+}
+
+#[test]
+fn matches_entry_part_of_speech() {
+    let jmdict = sample_jmdict();
+    assert!(matches_entry(
+        "食べました",
+        "食べる",
+        entry(&jmdict, "食べる")
+    ));
+    assert!(matches_entry(
+        "高くなかった",
+        "高い",
+        entry(&jmdict, "高い")
+    ));
+    assert!(matches_entry(
+        "勉強しました",
+        "勉強する",
+        entry(&jmdict, "勉強")
+    ));
+    assert!(matches_entry("行った", "行く", entry(&jmdict, "行く")));
+    // a noun has no conjugation
+    assert!(!matches_entry(
+        "食べました",
+        "食べる",
+        entry(&jmdict, "切手")
+    ));
+    // an adjective ending is not a verb ending
+    assert!(!matches_entry(
+        "高くなかった",
+        "高い",
+        entry(&jmdict, "食べる")
+    ));
+}
+
+#[test]
+fn matches_entry_original() {
+    let jmdict = sample_jmdict();
+    let original = &deinflect("切手")[0];
+    assert!(original.is_original());
+    assert!(original.matches_entry(entry(&jmdict, "切手")));
+}
+
+#[test]
+fn lookup_inflected_words() {
+    use self::Inflection::*;
+    let jmdict = sample_jmdict();
+    assert_eq!(
+        lookup_inflected(&jmdict, "食べました"),
+        (vec![1358280], vec![Polite, Past])
+    );
+    assert_eq!(
+        lookup_inflected(&jmdict, "食べませんでした"),
+        (vec![1358280], vec![Polite, Negative, Past])
+    );
+    assert_eq!(
+        lookup_inflected(&jmdict, "高くなかった"),
+        (vec![1279720], vec![Negative, Past])
+    );
+    assert_eq!(
+        lookup_inflected(&jmdict, "勉強しました"),
+        (vec![1238480], vec![Polite, Past])
+    );
+    assert_eq!(
+        lookup_inflected(&jmdict, "行った"),
+        (vec![1578850], vec![Past])
+    );
+}
+
+#[test]
+fn lookup_inflected_nouns() {
+    let jmdict = sample_jmdict();
+    // dictionary forms are not conjugated forms
+    assert_eq!(lookup_inflected(&jmdict, "切手"), (vec![], vec![]));
+    assert_eq!(lookup_inflected(&jmdict, "勉強"), (vec![], vec![]));
+    // きって looks like the te-form of きる, but is only a noun here
+    assert_eq!(lookup_inflected(&jmdict, "きって"), (vec![], vec![]));
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "reader")]
+
+use jmdict::diff::{diff, diff_entries, DictionaryDiff, EntryChange, EntryElement};
+use jmdict::prelude::*;
+
+const SAMPLE: &str = include_str!("data/jmdict.xml");
+
+/// Next release of the sample: one entry removed, one added and five modified.
+fn next_release() -> String {
+    let removed_start = SAMPLE.find("<entry>\n<ent_seq>1148600").unwrap();
+    let removed_end = removed_start + SAMPLE[removed_start..].find("</entry>\n").unwrap() + 9;
+    let mut xml = format!("{}{}", &SAMPLE[..removed_start], &SAMPLE[removed_end..]);
+    let edits = [
+        // priority of a kanji
+        ("<ke_pri>news2</ke_pri>", "<ke_pri>news1</ke_pri>"),
+        // kanji inserted before the others
+        (
+            "<ent_seq>1577980</ent_seq>\n",
+            "<ent_seq>1577980</ent_seq>\n<k_ele>\n<keb>入る</keb>\n</k_ele>\n",
+        ),
+        // second sense removed
+        ("<sense>\n<gloss>shamisen</gloss>\n</sense>\n", ""),
+        // gloss reworded
+        (
+            "<gloss>postage stamp</gloss>",
+            "<gloss>stamp (postage)</gloss>",
+        ),
+        // senses reordered, the second one inheriting the part of speech of the first
+        (
+            "<pos>&adj-i;</pos>\n<gloss>high</gloss>\n<gloss>tall</gloss>\n</sense>\n\
+             <sense>\n<gloss>expensive</gloss>",
+            "<pos>&adj-i;</pos>\n<gloss>expensive</gloss>\n</sense>\n\
+             <sense>\n<gloss>high</gloss>\n<gloss>tall</gloss>",
+        ),
+        // entry added
+        (
+            "</JMdict>",
+            "<entry>\n<ent_seq>1501110</ent_seq>\n<k_ele>\n<keb>犬</keb>\n</k_ele>\n\
+             <r_ele>\n<reb>いぬ</reb>\n</r_ele>\n<sense>\n<gloss>dog</gloss>\n</sense>\n\
+             </entry>\n</JMdict>",
+        ),
+    ];
+    for &(before, after) in &edits {
+        assert!(xml.contains(before), "{}", before);
+        xml = xml.replacen(before, after, 1);
+    }
+    xml
+}
+
+fn releases() -> (JMDict, JMDict) {
+    let old = JMDict::from_jmdict(SAMPLE.as_bytes()).unwrap();
+    let new = JMDict::from_jmdict(next_release().as_bytes()).unwrap();
+    (old, new)
+}
+
+fn find_change<'d, 'a>(
+    dictionary_diff: &'d DictionaryDiff<'a>,
+    sequence: u32,
+) -> &'d EntryChange<'a> {
+    dictionary_diff
+        .changes()
+        .iter()
+        .find(|change| change.sequence() == sequence)
+        .unwrap()
+}
+
+fn change_texts(change: &EntryChange) -> Vec<String> {
+    change
+        .field_changes()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn added_removed_and_modified_entries() {
+    let (old, new) = releases();
+    let dictionary_diff = diff(&old, &new);
+    let sequences: Vec<u32> = dictionary_diff
+        .changes()
+        .iter()
+        .map(EntryChange::sequence)
+        .collect();
+    assert_eq!(
+        sequences,
+        vec![1148600, 1301370, 1358280, 1467640, 1501110, 1577980]
+    );
+    let added: Vec<u32> = dictionary_diff.added().map(EntryChange::sequence).collect();
+    assert_eq!(added, vec![1501110]);
+    let removed: Vec<u32> = dictionary_diff
+        .removed()
+        .map(EntryChange::sequence)
+        .collect();
+    assert_eq!(removed, vec![1148600]);
+    assert_eq!(dictionary_diff.modified().count(), 4);
+    // reordered senses are not a change
+    assert!(diff_entries(
+        old.by_sequence(1279720).unwrap(),
+        new.by_sequence(1279720).unwrap()
+    )
+    .is_empty());
+    assert!(diff(&old, &old).is_empty());
+}
+
+#[test]
+fn field_changes() {
+    let (old, new) = releases();
+    let dictionary_diff = diff(&old, &new);
+    let texts = |sequence: u32| change_texts(find_change(&dictionary_diff, sequence));
+    assert_eq!(
+        texts(1358280),
+        vec!["kanji 1 priority: ichi1, news2, nf25 -> ichi1, news1, nf25"]
+    );
+    // the other kanji keep their text, only the inserted one is a change
+    assert_eq!(texts(1577980), vec!["kanji 1 text: (none) -> 入る"]);
+    // the senses after a removed one are not changed
+    assert_eq!(
+        texts(1467640),
+        vec![
+            "sense 2 glosses: shamisen -> (none)",
+            "sense 2 parts_of_speech: n -> (none)",
+        ]
+    );
+    // elements left unmatched in both releases are compared in order
+    assert_eq!(
+        texts(1301370),
+        vec!["sense 1 glosses: postage stamp -> stamp (postage)"]
+    );
+    let change = &find_change(&dictionary_diff, 1358280).field_changes()[0];
+    assert_eq!(change.element(), EntryElement::Kanji);
+    assert_eq!(change.number(), 1);
+    assert_eq!(change.field(), "priority");
+    assert_eq!(change.old_value(), Some("ichi1, news2, nf25"));
+    assert_eq!(change.new_value(), Some("ichi1, news1, nf25"));
+}
+
+#[test]
+fn affected_words() {
+    let (old, new) = releases();
+    let dictionary_diff = diff(&old, &new);
+    let word_list = vec![
+        JMDictEntryId::from_word("食べる"),
+        JMDictEntryId::from_word("ねこ"),
+        JMDictEntryId::from_word("ロックンロール"),
+        JMDictEntryId::from_word("犬"),
+        JMDictEntryId::from_word("飲む"),
+        JMDictEntryId::from_sequence(1270700),
+        JMDictEntryId::from_sequence(1577980),
+    ];
+    let affected: Vec<(&JMDictEntryId, u32)> = dictionary_diff
+        .affected_words(&word_list)
+        .into_iter()
+        .map(|(entry_id, change)| (entry_id, change.sequence()))
+        .collect();
+    assert_eq!(
+        affected,
+        vec![
+            (&word_list[0], 1358280),
+            (&word_list[1], 1467640),
+            (&word_list[2], 1148600),
+            (&word_list[3], 1501110),
+            (&word_list[6], 1577980),
+        ]
+    );
+}
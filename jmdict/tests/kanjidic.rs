@@ -0,0 +1,58 @@
+#![cfg(feature = "reader")]
+
+use jmdict::prelude::*;
+
+fn sample_kanjidic() -> Kanjidic {
+    Kanjidic::from_kanjidic(&include_bytes!("data/kanjidic2.xml")[..]).unwrap()
+}
+
+#[test]
+fn read_character() {
+    let kanjidic = sample_kanjidic();
+    assert_eq!(kanjidic.len(), 2);
+    let kanji = kanjidic.get('食').unwrap();
+    assert_eq!(kanji.literal(), '食');
+    // classical radical, not the Nelson one listed first
+    assert_eq!(kanji.radical(), Some(184));
+    // first stroke count, the others being common miscounts
+    assert_eq!(kanji.stroke_count(), Some(9));
+    assert_eq!(kanji.grade(), Some(2));
+    assert_eq!(kanji.frequency(), Some(328));
+    assert_eq!(kanji.jlpt(), Some(4));
+    assert_eq!(kanji.on_readings(), &["ショク", "ジキ"]);
+    assert_eq!(kanji.kun_readings(), &["く.う", "た.べる"]);
+    assert_eq!(kanji.nanori(), &["くら"]);
+    let meanings: Vec<(&str, Option<&str>)> = kanji
+        .meanings()
+        .iter()
+        .map(|meaning| (meaning.text(), meaning.lang()))
+        .collect();
+    assert_eq!(
+        meanings,
+        vec![("eat", None), ("food", None), ("manger", Some("fr"))]
+    );
+}
+
+#[test]
+fn read_character_without_grade_or_frequency() {
+    let kanjidic = sample_kanjidic();
+    let kanji = kanjidic.get('丼').unwrap();
+    assert_eq!(kanji.radical(), Some(3));
+    assert_eq!(kanji.stroke_count(), Some(5));
+    assert_eq!(kanji.grade(), None);
+    assert_eq!(kanji.frequency(), None);
+    assert_eq!(kanji.jlpt(), None);
+    assert_eq!(kanji.on_readings(), &["トン"]);
+    assert_eq!(kanji.kun_readings(), &["どんぶり"]);
+    assert!(kanji.nanori().is_empty());
+}
+
+#[test]
+fn characters_of_word() {
+    let kanjidic = sample_kanjidic();
+    let literals: Vec<char> = kanjidic
+        .characters_of("食べ物と丼")
+        .map(KanjiCharacter::literal)
+        .collect();
+    assert_eq!(literals, vec!['食', '丼']);
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "reader")]
+
+use jmdict::prelude::*;
+
+fn sample_entries(languages: &[&str]) -> Vec<JMDictEntry> {
+    let mut jmdict_reader = JMDictReader::new(&include_bytes!("data/jmdict.xml")[..]);
+    jmdict_reader.set_languages(languages.iter().map(|&l| String::from(l)).collect());
+    jmdict_reader
+        .collect::<Result<Vec<JMDictEntry>, jmdict::Error>>()
+        .unwrap()
+}
+
+fn entry(entries: &[JMDictEntry], sequence: u32) -> &JMDictEntry {
+    entries
+        .iter()
+        .find(|entry| entry.sequence() == sequence)
+        .unwrap()
+}
+
+/// Glosses of each sense, with their parts of speech as written in the entry.
+fn senses(entry: &JMDictEntry) -> Vec<(Vec<&str>, Vec<&str>)> {
+    entry
+        .senses()
+        .iter()
+        .map(|sense| {
+            (
+                sense.parts_of_speech().iter().map(|p| p.code()).collect(),
+                sense.glosses().iter().map(|g| g.text()).collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn all_languages() {
+    let entries = sample_entries(&[]);
+    assert_eq!(entry(&entries, 1467640).senses().len(), 5);
+}
+
+#[test]
+fn selected_languages() {
+    let entries = sample_entries(&["fre", "ger"]);
+    assert_eq!(
+        senses(entry(&entries, 1467640)),
+        vec![(vec!["n"], vec!["chat"]), (vec![], vec!["Katze", "Kater"])]
+    );
+    // glosses in one of the languages only
+    assert_eq!(
+        senses(entry(&entries, 1013420)),
+        vec![(vec!["n", "vs"], vec!["Arbeit"])]
+    );
+    assert_eq!(
+        senses(entry(&entries, 1577980)),
+        vec![(vec!["v1", "vi"], vec!["être"])]
+    );
+}
+
+#[test]
+fn english_fallback() {
+    let entries = sample_entries(&["fre"]);
+    // no French gloss in the entry: English glosses are kept
+    assert_eq!(
+        senses(entry(&entries, 1358280)),
+        vec![(vec!["v1", "vt"], vec!["to eat"])]
+    );
+    let entries = sample_entries(&["spa"]);
+    assert_eq!(
+        senses(entry(&entries, 1467640)),
+        vec![(vec!["n"], vec!["cat"]), (vec![], vec!["shamisen"])]
+    );
+}
+
+#[test]
+fn inherited_parts_of_speech() {
+    let entries = sample_entries(&["dut", "ger"]);
+    let neko = entry(&entries, 1467640);
+    assert_eq!(
+        senses(neko),
+        vec![(vec!["n"], vec!["kat"]), (vec![], vec!["Katze", "Kater"])]
+    );
+    for sense_index in 0..neko.senses().len() {
+        let parts_of_speech: Vec<&str> = neko
+            .sense_parts_of_speech(sense_index)
+            .iter()
+            .map(|p| p.code())
+            .collect();
+        assert_eq!(parts_of_speech, vec!["n"]);
+    }
+}
+
+#[test]
+fn select_languages_after_reading() {
+    let all_entries = sample_entries(&[]);
+    let selected_entries = sample_entries(&["fre", "ger"]);
+    for (mut entry, selected_entry) in all_entries.into_iter().zip(&selected_entries) {
+        entry.select_languages(&["fre", "ger"]);
+        assert!(jmdict::diff::diff_entries(&entry, selected_entry).is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "reader")]
+
+use jmdict::prelude::*;
+
+fn sample_jmdict() -> JMDict {
+    JMDict::from_jmdict(&include_bytes!("data/jmdict.xml")[..]).unwrap()
+}
+
+fn lookup_sequences(jmdict: &JMDict, word: &str) -> Vec<u32> {
+    jmdict
+        .lookup(&JMDictEntryId::from_word(word))
+        .into_iter()
+        .map(JMDictEntry::sequence)
+        .collect()
+}
+
+#[test]
+fn lookup_kanji_and_reading() {
+    let jmdict = sample_jmdict();
+    assert_eq!(lookup_sequences(&jmdict, "食べる"), vec![1358280]);
+    assert_eq!(lookup_sequences(&jmdict, "たべる"), vec![1358280]);
+    assert_eq!(lookup_sequences(&jmdict, "タベル"), vec![1358280]);
+    assert_eq!(lookup_sequences(&jmdict, "おん"), vec![1270700, 2117140]);
+    assert!(lookup_sequences(&jmdict, "飲む").is_empty());
+}
+
+#[test]
+fn lookup_romaji() {
+    let jmdict = sample_jmdict();
+    assert_eq!(lookup_sequences(&jmdict, "taberu"), vec![1358280]);
+    assert_eq!(lookup_sequences(&jmdict, "TABERU"), vec![1358280]);
+    assert!(lookup_sequences(&jmdict, "tabenai").is_empty());
+}
+
+#[test]
+fn lookup_romaji_spelling() {
+    let jmdict = sample_jmdict();
+    // exact spelling first, reading otherwise
+    assert_eq!(lookup_sequences(&jmdict, "ON"), vec![2117140]);
+    assert_eq!(lookup_sequences(&jmdict, "ＯＮ"), vec![2117140]);
+    assert_eq!(lookup_sequences(&jmdict, "on"), vec![1270700, 2117140]);
+    assert_eq!(lookup_sequences(&jmdict, "onn"), vec![1270700, 2117140]);
+}
+
+#[test]
+fn lookup_inflected_romaji() {
+    let jmdict = sample_jmdict();
+    let (entries, _) = jmdict.lookup_inflected("tabemashita");
+    let sequences: Vec<u32> = entries.into_iter().map(JMDictEntry::sequence).collect();
+    assert_eq!(sequences, vec![1358280]);
+}
+
+fn furigana_pairs(
+    word_lookup: &WordLookup,
+    candidate_index: usize,
+) -> Option<Vec<(&str, Option<&str>)>> {
+    word_lookup.furigana(candidate_index).map(|segments| {
+        segments
+            .iter()
+            .map(|segment| (segment.text(), segment.reading()))
+            .collect()
+    })
+}
+
+#[test]
+fn lookup_furigana() {
+    let jmdict = sample_jmdict();
+    let mut kanjidic = Kanjidic::new();
+    for &(literal, on_reading) in &[('勉', "ベン"), ('強', "キョウ")] {
+        let mut kanji = KanjiCharacter::new(literal);
+        kanji.add_on_reading(String::from(on_reading));
+        kanjidic.add_character(kanji);
+    }
+    let entry_id = JMDictEntryId::from_word("勉強");
+    let candidates = jmdict.lookup(&entry_id).into_iter().cloned().collect();
+    let mut word_lookup = WordLookup::new(entry_id, candidates);
+    assert!(word_lookup.furigana(0).is_none());
+    word_lookup.align_furigana(&kanjidic);
+    assert_eq!(
+        furigana_pairs(&word_lookup, 0),
+        Some(vec![("勉", Some("べん")), ("強", Some("きょう"))])
+    );
+    // without the readings of its kanji, a run keeps a single reading
+    word_lookup.align_furigana(&Kanjidic::new());
+    assert_eq!(
+        furigana_pairs(&word_lookup, 0),
+        Some(vec![("勉強", Some("べんきょう"))])
+    );
+    // kana-only words have no furigana
+    let entry_id = JMDictEntryId::from_word("アルバイト");
+    let candidates = jmdict.lookup(&entry_id).into_iter().cloned().collect();
+    let mut word_lookup = WordLookup::new(entry_id, candidates);
+    word_lookup.align_furigana(&kanjidic);
+    assert!(word_lookup.furigana(0).is_none());
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "reader")]
+
+use jmdict::prelude::*;
+use jmdict::tags::NameType;
+
+fn sample_jmnedict() -> JMnedict {
+    JMnedict::from_jmnedict(&include_bytes!("data/jmnedict.xml")[..]).unwrap()
+}
+
+fn lookup_sequences(jmnedict: &JMnedict, word: &str) -> Vec<u32> {
+    jmnedict
+        .lookup(&JMDictEntryId::from_word(word))
+        .into_iter()
+        .map(NameEntry::sequence)
+        .collect()
+}
+
+#[test]
+fn name_types() {
+    let jmnedict = sample_jmnedict();
+    assert_eq!(jmnedict.len(), 4);
+    let name_types = |sequence: u32| -> Vec<NameType> {
+        jmnedict
+            .by_sequence(sequence)
+            .unwrap()
+            .name_types()
+            .into_iter()
+            .cloned()
+            .collect()
+    };
+    assert_eq!(name_types(5082510), vec![NameType::Surname]);
+    // the kinds of name of every translation, without duplicates
+    assert_eq!(
+        name_types(5144390),
+        vec![NameType::Female, NameType::Surname, NameType::Place]
+    );
+    let entities = jmnedict.entities();
+    assert_eq!(
+        NameType::Station.description(entities),
+        Some("railway station")
+    );
+    assert_eq!(NameType::Surname.code(), "surname");
+}
+
+#[test]
+fn translations() {
+    let jmnedict = sample_jmnedict();
+    let tokyo = jmnedict.by_sequence(5609478).unwrap();
+    assert_eq!(tokyo.main_kanji().unwrap().string(), "東京");
+    assert_eq!(tokyo.main_reading().unwrap().string(), "とうきょう");
+    let translations = tokyo.translations();
+    assert_eq!(translations.len(), 2);
+    assert_eq!(translations[0].name_types(), &[NameType::Place]);
+    let details: Vec<(&str, &str)> = translations[0]
+        .details()
+        .iter()
+        .map(|detail| (detail.text(), detail.language()))
+        .collect();
+    assert_eq!(
+        details,
+        vec![("Tokyo (capital of Japan)", "eng"), ("Tokio", "ger")]
+    );
+    let cross_references = translations[0].cross_references();
+    assert_eq!(cross_references.len(), 1);
+    assert_eq!(cross_references[0].text(), "東京都");
+    assert_eq!(cross_references[0].reading(), None);
+    assert_eq!(translations[1].details()[0].text(), "Tokyo Station");
+    assert!(translations[1].cross_references().is_empty());
+}
+
+#[test]
+fn match_submitted_words() {
+    let jmnedict = sample_jmnedict();
+    assert_eq!(lookup_sequences(&jmnedict, "小泉"), vec![5082510]);
+    assert_eq!(lookup_sequences(&jmnedict, "桜"), vec![5144390]);
+    // readings match regardless of kana script, and may be typed in romaji
+    for word in &["さくら", "サクラ", "sakura"] {
+        assert_eq!(lookup_sequences(&jmnedict, word), vec![5144390, 5144400]);
+    }
+    // any reading of a name, not only its main one
+    assert_eq!(lookup_sequences(&jmnedict, "おう"), vec![5144390]);
+    assert!(lookup_sequences(&jmnedict, "大阪").is_empty());
+    assert!(lookup_sequences(&jmnedict, "東").is_empty());
+    let by_sequence: Vec<u32> = jmnedict
+        .lookup(&JMDictEntryId::from_sequence(5609478))
+        .into_iter()
+        .map(NameEntry::sequence)
+        .collect();
+    assert_eq!(by_sequence, vec![5609478]);
+    assert_eq!(jmnedict.by_kanji("東京").count(), 1);
+    assert_eq!(jmnedict.by_reading("こいずみ").count(), 1);
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "reader")]
+
+use jmdict::prelude::*;
+use jmdict::Error;
+
+const SAMPLE: &str = include_str!("data/jmdict.xml");
+
+fn read_entries(xml: &str) -> Vec<Result<JMDictEntry, Error>> {
+    JMDictReader::new(xml.as_bytes()).collect()
+}
+
+/// Sample file cut right after the end of its first entry.
+fn cut_after_first_entry() -> &'static str {
+    let end = SAMPLE.find("</entry>").unwrap() + "</entry>\n".len();
+    &SAMPLE[..end]
+}
+
+#[test]
+fn complete_file() {
+    let entries = read_entries(SAMPLE);
+    assert_eq!(entries.len(), 12);
+    assert!(entries.iter().all(Result::is_ok));
+}
+
+#[test]
+fn file_cut_between_entries() {
+    let entries = read_entries(cut_after_first_entry());
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].as_ref().unwrap().sequence(), 1358280);
+    match &entries[1] {
+        Err(Error::UnexpectedEof { element, .. }) => assert_eq!(element, "JMdict"),
+        other => panic!("expected an unexpected end of file, got {:?}", other),
+    }
+    assert!(JMDict::from_jmdict(cut_after_first_entry().as_bytes()).is_err());
+}
+
+#[test]
+fn file_cut_inside_entry() {
+    let end = SAMPLE.find("<keb>恩</keb>").unwrap();
+    let entries = read_entries(&SAMPLE[..end]);
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].is_ok());
+    match &entries[1] {
+        Err(Error::UnexpectedEof { .. }) => (),
+        other => panic!("expected an unexpected end of file, got {:?}", other),
+    }
+}
+
+#[test]
+fn invalid_sequence_number() {
+    let xml = SAMPLE.replace("<ent_seq>1270700</ent_seq>", "<ent_seq>12707OO</ent_seq>");
+    let entries = read_entries(&xml);
+    assert_eq!(entries.len(), 2);
+    match &entries[1] {
+        Err(Error::InvalidData { element, .. }) => assert_eq!(element, "ent_seq"),
+        other => panic!("expected invalid data, got {:?}", other),
+    }
+}
+
+#[test]
+fn empty_or_non_xml_file() {
+    for data in &["", "not a dictionary\n"] {
+        match JMDict::from_jmdict_any(data.as_bytes()) {
+            Err(Error::UnexpectedEof { element, .. }) => assert_eq!(element, "JMdict"),
+            other => panic!("expected an unexpected end of file, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn names_and_kanji_files_must_be_closed() {
+    let names = "<JMnedict>\n<entry>\n<ent_seq>5000000</ent_seq>\n</entry>\n";
+    let name_results: Vec<_> = JMnedictReader::new(names.as_bytes()).collect();
+    assert!(name_results[0].is_ok());
+    assert!(matches!(name_results[1], Err(Error::UnexpectedEof { .. })));
+    assert!(matches!(
+        KanjidicReader::new(&b""[..]).next(),
+        Some(Err(Error::UnexpectedEof { .. }))
+    ));
+}
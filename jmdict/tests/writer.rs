@@ -0,0 +1,87 @@
+#![cfg(feature = "reader")]
+
+use jmdict::prelude::*;
+use jmdict::writer::write_jmdict;
+
+/// Parse a JMdict file, returning its entries and entities.
+fn read_jmdict(xml: &[u8]) -> (Vec<JMDictEntry>, Entities) {
+    let mut jmdict_reader = JMDictReader::new(xml);
+    let entries = (&mut jmdict_reader)
+        .collect::<Result<Vec<JMDictEntry>, jmdict::Error>>()
+        .unwrap();
+    (entries, jmdict_reader.into_entities())
+}
+
+#[test]
+fn round_trip() {
+    let (entries, entities) = read_jmdict(include_bytes!("data/jmdict.xml"));
+    assert_eq!(entries.len(), 12);
+    let xml = write_jmdict(Vec::new(), &entities, &entries).unwrap();
+    let (written_entries, written_entities) = read_jmdict(&xml);
+    assert_eq!(written_entities, entities);
+    assert_eq!(format!("{:#?}", written_entries), format!("{:#?}", entries));
+    for (written_entry, entry) in written_entries.iter().zip(&entries) {
+        assert!(jmdict::diff::diff_entries(entry, written_entry).is_empty());
+    }
+    // written again identically
+    assert_eq!(
+        write_jmdict(Vec::new(), &written_entities, &written_entries).unwrap(),
+        xml
+    );
+}
+
+#[test]
+fn round_trip_fields() {
+    let (entries, entities) = read_jmdict(include_bytes!("data/jmdict.xml"));
+    let xml = write_jmdict(Vec::new(), &entities, &entries).unwrap();
+    let (written_entries, _) = read_jmdict(&xml);
+    let entry = |sequence: u32| {
+        written_entries
+            .iter()
+            .find(|entry| entry.sequence() == sequence)
+            .unwrap()
+    };
+    let iru = entry(1577980);
+    assert!(iru.readings()[2].no_kanji());
+    assert!(!iru.readings()[0].no_kanji());
+    assert_eq!(iru.readings()[1].restrictions().to_vec(), vec!["居る"]);
+    assert_eq!(iru.kanji()[1].info()[0].code(), "iK");
+    let humble_gloss = &iru.senses()[1].glosses()[0];
+    assert_eq!(humble_gloss.gloss_type().map(|t| t.code()), Some("expl"));
+    let arubaito = entry(1013420);
+    let language_source = &arubaito.senses()[0].language_sources()[0];
+    assert_eq!(language_source.lang(), Some("ger"));
+    assert_eq!(language_source.text(), "Arbeit");
+    let glosses = arubaito.senses()[0].glosses();
+    assert_eq!(glosses[1].gloss_type().map(|t| t.code()), Some("lit"));
+    assert_eq!(glosses[2].gender(), Some("fem"));
+    let waishatsu = entry(1147980);
+    let language_sources = waishatsu.senses()[0].language_sources();
+    assert!(language_sources[0].partial() && language_sources[0].wasei());
+    assert_eq!(language_sources[1].lang(), Some("dut"));
+    assert_eq!(language_sources[1].text(), "");
+    let rock = entry(1148600);
+    assert_eq!(rock.senses()[0].glosses()[0].text(), "rock & roll");
+    assert_eq!(
+        rock.senses()[0].glosses()[1].text(),
+        "\"rock 'n' roll\" <music>"
+    );
+}
+
+#[test]
+fn entity_escaping() {
+    let mut entities = Entities::new();
+    entities.insert(
+        String::from("n"),
+        String::from("noun \"futsuumeishi\" & 100% <common>"),
+    );
+    entities.insert(String::from("amp"), String::from("&#38; &amp;"));
+    let xml = write_jmdict(Vec::new(), &entities, &[] as &[JMDictEntry]).unwrap();
+    let (entries, written_entities) = read_jmdict(&xml);
+    assert!(entries.is_empty());
+    assert_eq!(
+        written_entities.get("n"),
+        Some("noun \"futsuumeishi\" & 100% <common>")
+    );
+    assert_eq!(written_entities.get("amp"), Some("&#38; &amp;"));
+}